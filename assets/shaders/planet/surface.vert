@@ -1,5 +1,6 @@
 out VS_OUT {
     vec3 normal;
+    vec3 color;
     vec2 texCoord;
     float brightness;
     float temperature;
@@ -15,6 +16,7 @@ void main()
 {
     gl_Position = projection * view * model * vec4(aPos, 1.0);
     vs_out.normal = vec3(model * vec4(aNormal, 1.0));
+    vs_out.color = aColor;
     vs_out.texCoord = aTexCoord;
 
     vs_out.brightness = aBrightness;
@@ -105,9 +105,9 @@ impl<'a, V> ShaderTarget<'a, V> where V: Vertex {
 
     pub fn set_rotation(&mut self, axis: Axis, deg: Deg<f32>) {
         match axis {
-            Axis::X => {},
+            Axis::X => self.model_matrix = self.model_matrix * Matrix4::from_angle_x(deg),
             Axis::Y => self.model_matrix = self.model_matrix * Matrix4::from_angle_y(deg),
-            Axis::Z => {},
+            Axis::Z => self.model_matrix = self.model_matrix * Matrix4::from_angle_z(deg),
         }
     }
 }
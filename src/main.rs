@@ -2,8 +2,14 @@ extern crate glfw;
 extern crate gl;
 extern crate image;
 extern crate cgmath;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
 
-use planet_gen::planet::PlanetVertex;
+use planet_gen::planet::{ PlanetVertex, PlanetType };
+use planet_gen::preset::PlanetParams;
+use planet_gen::material::MaterialRegistry;
+use planet_gen::noise_graph::NoiseGraph;
 use cgmath::{ Zero, Vector3 };
 
 #[macro_use]
@@ -59,16 +65,15 @@ pub fn main() {
 }
 
 fn create_planet<'a>(grid: Grid, surface_shader: &'a ShaderProgram<PlanetVertex>, atmosphere_shader: &'a ShaderProgram<PlanetVertex>) -> Planet<'a> {
-    let mut grid = grid;
-
-    let light_direction = Vector3::new(2.0, 0.0, 0.0);
-    Landscape::fill_heights(&mut grid, 1000.0, 500.0);
+    let params = PlanetParams {
+        materials: MaterialRegistry::load("assets/materials.toml"),
+        terrain: NoiseGraph::load("assets/terrain.toml"),
+        ..PlanetParams::preset(PlanetType::EarthLike)
+    };
 
-    let mut planet_mesh = Planet::create(grid, light_direction, surface_shader, atmosphere_shader);
+    let mut planet_mesh = Planet::generate(grid, &params, surface_shader, atmosphere_shader);
     planet_mesh.compile();
     planet_mesh.set_pos(Vector3::zero());
-    planet_mesh.set_light(light_direction, Vector3::new(1.0, 1.0, 1.0));
-    planet_mesh.set_sea_level(500.0);
 
     planet_mesh
 }
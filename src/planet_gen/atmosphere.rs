@@ -0,0 +1,176 @@
+use std::f64;
+
+use sphere_grid::grid::Grid;
+use sphere_grid::tile::PlanetTile;
+use planet_gen::landscape::Landscape;
+
+/* Gray atmosphere of equal layers stacked above every tile. Each layer absorbs a share of the longwave
+   radiation passing through it and re-emits it both up and down, the lowest one also exchanges sensible heat
+   with the surface. Layer temperatures are kept in PlanetTile::atmosphere_temperatures, in °C */
+#[derive(Copy, Clone, Debug)]
+pub struct Atmosphere {
+    pub layers: usize,
+    pub optical_depth: f64, // longwave, of the whole column
+    pub shortwave_absorption: f64, // fraction of the sunlight absorbed by the air
+    pub shortwave_reflection: f64, // fraction of the sunlight reflected by clouds and haze
+
+    pub surface_pressure: f64, // Pa, at sea level
+    pub scale_height: f64, // m
+    pub lapse_rate: f64, // °C per m of elevation
+    pub gravity: f64, // m/s^2
+    pub specific_heat: f64, // J / (kg K)
+    pub sensible_heat_exchange: f64, // W / (m^2 K) between the surface and the lowest layer
+}
+
+impl Default for Atmosphere {
+    fn default() -> Self {
+        Atmosphere::earth()
+    }
+}
+
+#[allow(dead_code)]
+impl Atmosphere {
+    pub fn earth() -> Atmosphere {
+        Atmosphere {
+            layers: 1,
+            optical_depth: 1.5,
+            shortwave_absorption: 0.2,
+            shortwave_reflection: 0.0,
+
+            surface_pressure: 101325.0,
+            scale_height: 8500.0,
+            lapse_rate: 0.0065,
+            gravity: 9.81,
+            specific_heat: 1004.0,
+            sensible_heat_exchange: 10.0,
+        }
+    }
+
+    pub fn mars() -> Atmosphere {
+        Atmosphere {
+            layers: 1,
+            optical_depth: 0.15,
+            shortwave_absorption: 0.05,
+            shortwave_reflection: 0.0,
+
+            surface_pressure: 610.0,
+            scale_height: 11100.0,
+            lapse_rate: 0.0025,
+            gravity: 3.71,
+            specific_heat: 770.0,
+            sensible_heat_exchange: 1.0,
+        }
+    }
+
+    pub fn venus() -> Atmosphere {
+        Atmosphere {
+            layers: 30,
+            optical_depth: 100.0,
+            shortwave_absorption: 0.1,
+            shortwave_reflection: 0.7,
+
+            surface_pressure: 9.2e6,
+            scale_height: 15900.0,
+            lapse_rate: 0.0078,
+            gravity: 8.87,
+            specific_heat: 1100.0,
+            sensible_heat_exchange: 20.0,
+        }
+    }
+
+    /* Airless body, the surface radiates straight to space */
+    pub fn none() -> Atmosphere {
+        Atmosphere {
+            layers: 0,
+            optical_depth: 0.0,
+            shortwave_absorption: 0.0,
+            shortwave_reflection: 0.0,
+
+            surface_pressure: 0.0,
+            scale_height: 1.0,
+            lapse_rate: 0.0,
+            gravity: 9.81,
+            specific_heat: 1004.0,
+            sensible_heat_exchange: 0.0,
+        }
+    }
+
+    pub fn layer_emissivity(&self) -> f64 {
+        if self.layers == 0 { 0.0 } else { 1.0 - (-self.optical_depth / self.layers as f64).exp() }
+    }
+
+    /* Pa at the given height over sea level (m) */
+    pub fn pressure_at(&self, elevation: f64) -> f64 {
+        self.surface_pressure * (-f64::max(0.0, elevation) / self.scale_height).exp()
+    }
+
+    /* Surface pressure and the air temperature climate, biomes and ice see: the surface temperature
+       lowered by the lapse rate over land above sea level */
+    pub fn apply_elevation(&self, grid: &mut Grid, sea_level: f64) {
+        for t in &mut grid.tiles {
+            let elevation = if t.has_water { 0.0 } else { f64::max(0.0, t.height - sea_level) };
+
+            t.pressure = self.pressure_at(elevation);
+            t.air_temperature = t.temperature - self.lapse_rate * elevation;
+        }
+    }
+
+    /* J / (m^2 K) of a single layer */
+    pub fn layer_heat_capacity(&self) -> f64 {
+        self.surface_pressure / self.gravity * self.specific_heat / self.layers as f64
+    }
+
+    /* Advances the layers above one tile by delta_t. absorbed_shortwave is the sunlight taken by the air (W/m^2).
+       Returns the longwave and sensible flux into the surface (negative when the surface loses heat)
+       and the longwave flux leaving to space */
+    pub fn exchange(&self, tile: &mut PlanetTile, surface_emissivity: f64, absorbed_shortwave: f64, delta_t: f64) -> (f64, f64) {
+        let surface_emission = surface_emissivity * Self::black_body(tile.temperature);
+
+        if self.layers == 0 {
+            return (-surface_emission, surface_emission);
+        }
+
+        if tile.atmosphere_temperatures.len() != self.layers {
+            tile.atmosphere_temperatures = vec![tile.temperature; self.layers];
+        }
+
+        let emissivity = self.layer_emissivity();
+        let emissions: Vec<f64> = tile.atmosphere_temperatures.iter().map(|t| emissivity * Self::black_body(*t)).collect();
+        let mut net: Vec<f64> = emissions.iter().map(|e| absorbed_shortwave / self.layers as f64 - 2.0 * e).collect();
+
+        let mut upward = surface_emission;
+        for i in 0..self.layers {
+            net[i] += emissivity * upward;
+            upward = (1.0 - emissivity) * upward + emissions[i];
+        }
+
+        let mut downward = 0.0;
+        for i in (0..self.layers).rev() {
+            net[i] += emissivity * downward;
+            downward = (1.0 - emissivity) * downward + emissions[i];
+        }
+
+        let sensible = self.sensible_heat_exchange * (tile.atmosphere_temperatures[0] - tile.temperature);
+        net[0] -= sensible;
+
+        let capacity = self.layer_heat_capacity();
+        for i in 0..self.layers {
+            tile.atmosphere_temperatures[i] += net[i] * delta_t / capacity;
+        }
+
+        (surface_emissivity * downward + sensible - surface_emission, upward)
+    }
+
+    /* W / (m^2 K) the flux into the surface falls by for every degree the surface warms, from its own emission
+       and the sensible heat it gives to the air */
+    pub fn surface_coupling(&self, surface_emissivity: f64, temperature: f64) -> f64 {
+        let sensible = if self.layers == 0 { 0.0 } else { self.sensible_heat_exchange };
+
+        4.0 * surface_emissivity * Landscape::STEFAN_BOLTZMANN * (273.15 + temperature).powi(3) + sensible
+    }
+
+    /* W/m^2 emitted by a black body at the given temperature in °C */
+    fn black_body(temperature: f64) -> f64 {
+        (273.15 + temperature).powf(4.0) * Landscape::STEFAN_BOLTZMANN
+    }
+}
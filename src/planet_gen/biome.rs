@@ -0,0 +1,146 @@
+extern crate cgmath;
+
+use cgmath::Vector3;
+
+use sphere_grid::grid::Grid;
+use sphere_grid::tile::PlanetTile;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Deserialize)]
+pub enum Biome {
+    Ocean,
+    OceanShelf,
+    SeaIce,
+    Ice,
+    Tundra,
+    Alpine,
+    BorealForest,
+    Desert,
+    TemperateGrassland,
+    TemperateForest,
+    TemperateRainforest,
+    Savanna,
+    TropicalSeasonalForest,
+    TropicalRainforest,
+}
+
+impl Biome {
+    pub fn color(&self) -> Vector3<f32> {
+        match self {
+            Biome::Ocean => Vector3::new(0.05, 0.15, 0.45),
+            Biome::OceanShelf => Vector3::new(0.15, 0.35, 0.65),
+            Biome::SeaIce => Vector3::new(0.80, 0.90, 0.95),
+            Biome::Ice => Vector3::new(0.95, 0.95, 1.00),
+            Biome::Tundra => Vector3::new(0.60, 0.65, 0.55),
+            Biome::Alpine => Vector3::new(0.55, 0.50, 0.50),
+            Biome::BorealForest => Vector3::new(0.20, 0.40, 0.30),
+            Biome::Desert => Vector3::new(0.90, 0.80, 0.50),
+            Biome::TemperateGrassland => Vector3::new(0.65, 0.75, 0.35),
+            Biome::TemperateForest => Vector3::new(0.25, 0.55, 0.20),
+            Biome::TemperateRainforest => Vector3::new(0.10, 0.45, 0.30),
+            Biome::Savanna => Vector3::new(0.75, 0.70, 0.30),
+            Biome::TropicalSeasonalForest => Vector3::new(0.35, 0.60, 0.10),
+            Biome::TropicalRainforest => Vector3::new(0.05, 0.40, 0.05),
+        }
+    }
+}
+
+/* Whittaker-style classifier on the mean climate (PlanetTile::mean_air_temperature, mean_precipitation), so
+   biomes follow neither the day nor the season: temperature bands are split by precipitation. Temperatures are in
+   °C, precipitation in kg/m^2/s, heights and depths are in the same units as PlanetTile::height */
+#[derive(Copy, Clone, Debug)]
+pub struct BiomeClassifier {
+    pub sea_ice_temperature: f64,
+    pub ice_temperature: f64,
+    pub tundra_temperature: f64,
+    pub boreal_temperature: f64,
+    pub tropical_temperature: f64,
+
+    pub arid_precipitation: f64,
+    pub semiarid_precipitation: f64,
+    pub humid_precipitation: f64,
+
+    pub sea_ice_thickness: f64, // m
+    pub glacier_thickness: f64, // m
+
+    pub shelf_depth: f64,
+    pub alpine_elevation: f64,
+}
+
+impl Default for BiomeClassifier {
+    fn default() -> Self {
+        BiomeClassifier {
+            sea_ice_temperature: -2.0,
+            ice_temperature: -15.0,
+            tundra_temperature: -5.0,
+            boreal_temperature: 5.0,
+            tropical_temperature: 20.0,
+
+            arid_precipitation: 8.0e-6, // 250 mm a year
+            semiarid_precipitation: 2.4e-5, // 750 mm a year
+            humid_precipitation: 6.3e-5, // 2000 mm a year
+
+            sea_ice_thickness: 0.1,
+            glacier_thickness: 10.0,
+
+            shelf_depth: 150.0,
+            alpine_elevation: 3000.0,
+        }
+    }
+}
+
+impl BiomeClassifier {
+    pub fn classify(&self, tile: &PlanetTile, sea_level: f64) -> Biome {
+        let t = tile.mean_air_temperature;
+        let p = tile.mean_precipitation;
+
+        if tile.has_water {
+            return if t < self.sea_ice_temperature || tile.ice_thickness >= self.sea_ice_thickness {
+                Biome::SeaIce
+            } else if sea_level - tile.height < self.shelf_depth {
+                Biome::OceanShelf
+            } else {
+                Biome::Ocean
+            };
+        }
+
+        if t < self.ice_temperature || tile.ice_thickness >= self.glacier_thickness {
+            return Biome::Ice;
+        }
+
+        if tile.height - sea_level > self.alpine_elevation {
+            return Biome::Alpine;
+        }
+
+        if t < self.tundra_temperature {
+            Biome::Tundra
+        } else if t < self.boreal_temperature {
+            if p < self.arid_precipitation { Biome::Tundra } else { Biome::BorealForest }
+        } else if t < self.tropical_temperature {
+            if p < self.arid_precipitation {
+                Biome::Desert
+            } else if p < self.semiarid_precipitation {
+                Biome::TemperateGrassland
+            } else if p < self.humid_precipitation {
+                Biome::TemperateForest
+            } else {
+                Biome::TemperateRainforest
+            }
+        } else {
+            if p < self.arid_precipitation {
+                Biome::Desert
+            } else if p < self.semiarid_precipitation {
+                Biome::Savanna
+            } else if p < self.humid_precipitation {
+                Biome::TropicalSeasonalForest
+            } else {
+                Biome::TropicalRainforest
+            }
+        }
+    }
+
+    pub fn classify_grid(&self, grid: &mut Grid, sea_level: f64) {
+        for t in &mut grid.tiles {
+            t.biome = self.classify(t, sea_level);
+        }
+    }
+}
@@ -0,0 +1,136 @@
+extern crate cgmath;
+
+use std::f64;
+use cgmath::{ Vector3, InnerSpace, Zero };
+
+use sphere_grid::grid::Grid;
+use sphere_grid::tile::GridTile;
+
+use planet_gen::transport::Transport;
+
+#[allow(dead_code)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum CirculationModel {
+    /* Hadley, Ferrel and polar cells from latitude alone, collapses into one Hadley cell on slow rotators */
+    ThreeCell,
+    /* Warm air gives low pressure, winds follow the pressure gradient balanced by Coriolis and surface drag */
+    PressureGradient,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Circulation {
+    pub model: CirculationModel,
+    pub rotation_rate: f64, // rad/s, negative for retrograde rotation
+
+    pub wind_speed: f64, // m/s, peak speed of the three-cell bands
+    pub pressure_sensitivity: f64, // Pa per °C of deviation from the global mean temperature
+    pub air_density: f64, // kg/m^3
+    pub surface_drag: f64, // 1/s
+
+    pub heat_transport: f64, // fraction of the wind speed at which the air layers carry their temperature
+}
+
+impl Default for Circulation {
+    fn default() -> Self {
+        Circulation {
+            model: CirculationModel::ThreeCell,
+            rotation_rate: Circulation::EARTH_ROTATION_RATE,
+
+            wind_speed: 8.0,
+            pressure_sensitivity: 50.0,
+            air_density: 1.2,
+            surface_drag: 1.0e-5,
+
+            heat_transport: 0.1,
+        }
+    }
+}
+
+impl Circulation {
+    pub const EARTH_ROTATION_RATE: f64 = 7.2921e-5; // rad/s
+
+    /* Below this fraction of Earth's rotation rate the Coriolis force can't split the Hadley cell */
+    const SINGLE_CELL_ROTATION: f64 = 0.25;
+
+    pub fn update(&self, grid: &mut Grid, radius: f64, delta_t: f64) {
+        let winds: Vec<Vector3<f32>> = match self.model {
+            CirculationModel::ThreeCell => grid.tiles.iter().map(|t| self.three_cell_wind(t.grid_tile.pos)).collect(),
+            CirculationModel::PressureGradient => self.pressure_gradient_winds(grid, radius),
+        };
+
+        for i in 0..grid.tiles.len() {
+            grid.tiles[i].wind = winds[i];
+        }
+
+        /* The wind moves the air (PlanetTile::atmosphere_temperatures), not the ground under it. The surface feels
+           the change through its exchange with the lowest layer, so nothing moves on an airless planet */
+        let layers = grid.tiles.iter().map(|t| t.atmosphere_temperatures.len()).min().unwrap_or(0);
+
+        if self.heat_transport > 0.0 && layers > 0 {
+            let velocities: Vec<Vector3<f32>> = winds.iter().map(|w| w * self.heat_transport as f32).collect();
+
+            for layer in 0..layers {
+                let temperatures: Vec<f64> = grid.tiles.iter().map(|t| t.atmosphere_temperatures[layer]).collect();
+                let temperatures = Transport::advect_scalar(grid, &temperatures, &velocities, radius, delta_t);
+
+                for i in 0..grid.tiles.len() {
+                    grid.tiles[i].atmosphere_temperatures[layer] = temperatures[i];
+                }
+            }
+        }
+    }
+
+    pub fn coriolis_parameter(&self, pos: Vector3<f32>) -> f64 {
+        2.0 * self.rotation_rate * pos.y as f64
+    }
+
+    fn three_cell_wind(&self, pos: Vector3<f32>) -> Vector3<f32> {
+        let (east, north) = Transport::local_basis(pos);
+
+        let latitude = (pos.y as f64).max(-1.0).min(1.0).asin();
+        let rotation = self.rotation_rate / Circulation::EARTH_ROTATION_RATE;
+
+        let cells = if rotation.abs() < Circulation::SINGLE_CELL_ROTATION { 1.0 } else { 3.0 };
+        let strength = (2.0 * cells * latitude.abs()).sin() * self.wind_speed;
+
+        /* Equatorward flow is turned westward (and poleward flow eastward) in the direction opposite to rotation */
+        let zonal = -f64::min(1.0, rotation.abs()) * rotation.signum();
+        let meridional = -0.3 * latitude.signum();
+
+        (east * zonal as f32 + north * meridional as f32) * strength as f32
+    }
+
+    fn pressure_gradient_winds(&self, grid: &Grid, radius: f64) -> Vec<Vector3<f32>> {
+        let mean_temperature = grid.tiles.iter().map(|t| t.temperature).sum::<f64>() / grid.tiles.len() as f64;
+        let pressures: Vec<f64> = grid.tiles.iter().map(|t| -self.pressure_sensitivity * (t.temperature - mean_temperature)).collect();
+
+        let mut winds = Vec::with_capacity(grid.tiles.len());
+
+        for i in 0..grid.tiles.len() {
+            let tile = &grid.tiles[i].grid_tile;
+            let mut gradient: Vector3<f64> = Vector3::zero();
+
+            for k in 0..tile.edge_count as usize {
+                let offset = GridTile::get_pos(tile.tiles[k]) - tile.pos;
+                let offset = Vector3::new(offset.x as f64, offset.y as f64, offset.z as f64);
+                let distance = offset.magnitude() * radius;
+
+                gradient += offset.normalize() * ((pressures[GridTile::get_id(tile.tiles[k])] - pressures[i]) / distance);
+            }
+
+            gradient = gradient * (2.0 / tile.edge_count as f64);
+
+            /* Steady state of drag * v + f * (up x v) = -grad(p) / density */
+            let up = Vector3::new(tile.pos.x as f64, tile.pos.y as f64, tile.pos.z as f64);
+            let force = -gradient / self.air_density;
+            let f = self.coriolis_parameter(tile.pos);
+            let r = self.surface_drag;
+
+            let wind = (force * r - up.cross(force) * f) / (r * r + f * f);
+
+            winds.push(Vector3::new(wind.x as f32, wind.y as f32, wind.z as f32));
+        }
+
+        winds
+    }
+}
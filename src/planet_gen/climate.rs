@@ -0,0 +1,23 @@
+use std::f64;
+
+use sphere_grid::grid::Grid;
+
+/* Running means of the climate on every tile (PlanetTile::mean_air_temperature, mean_precipitation), so slower
+   processes see neither the time of day nor the season. Until averaging_time has passed every step counts the same */
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ClimateMeans {
+    elapsed: f64, // s
+}
+
+impl ClimateMeans {
+    /* averaging_time in s, a year or a day, whichever is longer */
+    pub fn update(&mut self, grid: &mut Grid, averaging_time: f64, delta_t: f64) {
+        self.elapsed += delta_t;
+        let weight = delta_t / f64::min(self.elapsed, averaging_time);
+
+        for t in &mut grid.tiles {
+            t.mean_air_temperature += weight * (t.air_temperature - t.mean_air_temperature);
+            t.mean_precipitation += weight * (t.precipitation - t.mean_precipitation);
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::fmt;
+
+/* Runs the simulation in fixed steps of time_step simulated seconds, independent of the frame rate.
+   Real time between frames is collected in an accumulator and paid out as whole steps, at most
+   max_steps_per_frame times the speed at once so a slow frame drops the backlog instead of freezing the window */
+#[derive(Copy, Clone, Debug)]
+pub struct SimulationClock {
+    pub time_step: f64, // simulated s per step
+    pub steps_per_second: f64, // steps per real second at speed 1
+    pub speed: f64,
+    pub max_steps_per_frame: u32,
+
+    time: f64,
+    accumulator: f64,
+    last_real_time: Option<f64>,
+    paused: bool,
+    pending_steps: u32,
+}
+
+impl Default for SimulationClock {
+    fn default() -> Self {
+        SimulationClock {
+            time_step: 240.0,
+            steps_per_second: 60.0,
+            speed: 1.0,
+            max_steps_per_frame: 8,
+
+            time: 0.0,
+            accumulator: 0.0,
+            last_real_time: None,
+            paused: false,
+            pending_steps: 0,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl SimulationClock {
+    pub const MIN_SPEED: f64 = 1.0 / 64.0;
+    pub const MAX_SPEED: f64 = 64.0;
+
+    /* Number of steps to run for a frame drawn at real_time (s). Each step is taken with tick() */
+    pub fn advance(&mut self, real_time: f64) -> u32 {
+        let elapsed = match self.last_real_time {
+            Some(last) => f64::max(0.0, real_time - last),
+            None => 0.0,
+        };
+        self.last_real_time = Some(real_time);
+
+        let mut steps = self.pending_steps;
+        self.pending_steps = 0;
+
+        if !self.paused {
+            self.accumulator += elapsed * self.steps_per_second * self.speed;
+
+            let due = self.accumulator.floor();
+            let max_steps = (self.max_steps_per_frame as f64 * f64::max(1.0, self.speed)).ceil();
+            if due > max_steps {
+                steps += max_steps as u32;
+                self.accumulator = 0.0;
+            } else {
+                steps += due as u32;
+                self.accumulator -= due;
+            }
+        }
+
+        steps
+    }
+
+    pub fn tick(&mut self) {
+        self.time += self.time_step;
+    }
+
+    /* Seconds of simulated time since the northern spring equinox of year 0 */
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn set_time(&mut self, time: f64) {
+        self.time = time;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        self.accumulator = 0.0;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        let paused = !self.paused;
+        self.set_paused(paused);
+    }
+
+    /* Runs one step on the next frame, meant to be used while paused */
+    pub fn single_step(&mut self) {
+        self.pending_steps += 1;
+    }
+
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed.max(Self::MIN_SPEED).min(Self::MAX_SPEED);
+    }
+
+    pub fn faster(&mut self) {
+        let speed = self.speed * 2.0;
+        self.set_speed(speed);
+    }
+
+    pub fn slower(&mut self) {
+        let speed = self.speed / 2.0;
+        self.set_speed(speed);
+    }
+
+    pub fn date(&self, day_length: f64, year_length: f64) -> SimulationDate {
+        let year = (self.time / year_length).floor();
+        let time_of_year = self.time - year * year_length;
+        let day = (time_of_year / day_length).floor();
+        let seconds = (time_of_year - day * day_length) / day_length * 86400.0;
+
+        SimulationDate {
+            year: year as i64,
+            day: day as u32 + 1,
+            hour: (seconds / 3600.0) as u32,
+            minute: (seconds % 3600.0 / 60.0) as u32,
+            second: (seconds % 60.0) as u32,
+        }
+    }
+}
+
+/* Calendar of the simulated planet, hours are 1/24 of its day */
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SimulationDate {
+    pub year: i64,
+    pub day: u32, // of the year, from 1
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+}
+
+impl fmt::Display for SimulationDate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Year {}, day {}, {:02}:{:02}:{:02}", self.year, self.day, self.hour, self.minute, self.second)
+    }
+}
@@ -0,0 +1,156 @@
+extern crate cgmath;
+extern crate rand;
+
+use std::f64::consts::PI;
+
+use self::cgmath::{ Vector3, InnerSpace };
+use self::rand::{ Rng, SeedableRng };
+use self::rand::rngs::StdRng;
+
+use sphere_grid::grid::Grid;
+use sphere_grid::sphere::Sphere;
+
+/* Impact craters stamped onto the heights, oldest first so later ones cut through and bury earlier ones.
+   Diameters follow a power law, N(> D) ~ D^-size_exponent. Small craters are simple bowls 0.2 D deep, above
+   transition_diameter they flatten out as complex craters do */
+#[derive(Copy, Clone, Debug)]
+pub struct CraterField {
+    pub seed: u64,
+    pub count: usize,
+    pub min_diameter: f64, // m
+    pub max_diameter: f64, // m
+    pub size_exponent: f64,
+
+    pub depth_ratio: f64, // depth / diameter of simple craters
+    pub transition_diameter: f64, // m
+    pub rim_ratio: f64, // rim height / depth
+    pub ejecta_extent: f64, // crater radii reached by the ejecta blanket
+}
+
+impl Default for CraterField {
+    fn default() -> Self {
+        CraterField::moon()
+    }
+}
+
+#[allow(dead_code)]
+impl CraterField {
+    pub fn moon() -> CraterField {
+        CraterField {
+            seed: 0,
+            count: 600,
+            min_diameter: 40000.0,
+            max_diameter: 900000.0,
+            size_exponent: 2.0,
+
+            depth_ratio: 0.2,
+            transition_diameter: 15000.0,
+            rim_ratio: 0.3,
+            ejecta_extent: 3.0,
+        }
+    }
+
+    /* Higher gravity gives shallower complex craters from smaller sizes on */
+    pub fn mercury() -> CraterField {
+        CraterField {
+            seed: 1,
+            count: 500,
+            transition_diameter: 10000.0,
+            ejecta_extent: 2.5,
+            ..CraterField::moon()
+        }
+    }
+
+    /* planet_radius in m, the heights of the grid in m */
+    pub fn stamp(&self, grid: &mut Grid, planet_radius: f64) {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+
+        for _ in 0..self.count {
+            let centre = Sphere::random_direction(&mut rng);
+            let diameter = self.random_diameter(&mut rng);
+            self.stamp_crater(grid, centre, diameter, planet_radius);
+        }
+    }
+
+    pub fn depth(&self, diameter: f64) -> f64 {
+        if diameter < self.transition_diameter {
+            self.depth_ratio * diameter
+        } else {
+            self.depth_ratio * self.transition_diameter * (diameter / self.transition_diameter).powf(0.3)
+        }
+    }
+
+    /* Height relative to the surface before the impact at distance r (in crater radii) from the centre */
+    pub fn profile(&self, r: f64, depth: f64) -> f64 {
+        let rim = self.rim_ratio * depth;
+
+        if r < 1.0 {
+            -depth + (depth + rim) * r * r
+        } else if r < self.ejecta_extent {
+            /* Blanket thinning with the cube of the distance, brought to zero at its edge */
+            let edge = self.ejecta_extent.powi(-3);
+            rim * (r.powi(-3) - edge) / (1.0 - edge)
+        } else {
+            0.0
+        }
+    }
+
+    fn stamp_crater(&self, grid: &mut Grid, centre: Vector3<f32>, diameter: f64, planet_radius: f64) {
+        let radius = 0.5 * diameter;
+        let depth = self.depth(diameter);
+        let reach = f64::min(PI, self.ejecta_extent * radius / planet_radius); // radians
+
+        /* The bowl replaces what was there, measured from the mean height under it */
+        let cos_radius = (radius / planet_radius).min(PI).cos();
+        let mut base = 0.0;
+        let mut base_area = 0.0;
+        for t in &grid.tiles {
+            if (t.grid_tile.pos.dot(centre) as f64) < cos_radius {
+                continue;
+            }
+
+            let r = Sphere::geodesic_distance(centre, t.grid_tile.pos, planet_radius) / radius;
+            if r < 1.0 {
+                let area = t.grid_tile.area() as f64;
+                base += t.height * area;
+                base_area += area;
+            }
+        }
+
+        if base_area == 0.0 {
+            /* Smaller than a tile, only the nearest one is hit */
+            if let Some(t) = grid.tiles.iter_mut().max_by(|a, b| a.grid_tile.pos.dot(centre).partial_cmp(&b.grid_tile.pos.dot(centre)).unwrap()) {
+                t.height -= depth;
+            }
+            return;
+        }
+        base /= base_area;
+
+        let cos_reach = reach.cos();
+        for t in &mut grid.tiles {
+            if (t.grid_tile.pos.dot(centre) as f64) < cos_reach {
+                continue;
+            }
+
+            let r = Sphere::geodesic_distance(centre, t.grid_tile.pos, planet_radius) / radius;
+            let relief = self.profile(r, depth);
+
+            if r < 1.0 {
+                /* Fully reset in the bowl, blending back to the old surface towards the rim */
+                let reset = f64::min(1.0, 2.0 * (1.0 - r));
+                t.height = reset * (base + relief) + (1.0 - reset) * (t.height + relief);
+            } else {
+                t.height += relief;
+            }
+        }
+    }
+
+
+    /* Inverse of the truncated power law */
+    fn random_diameter(&self, rng: &mut StdRng) -> f64 {
+        let u: f64 = rng.gen();
+        let ratio = (self.min_diameter / self.max_diameter).powf(self.size_exponent);
+
+        self.min_diameter * (1.0 - u * (1.0 - ratio)).powf(-1.0 / self.size_exponent)
+    }
+}
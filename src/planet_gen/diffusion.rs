@@ -0,0 +1,93 @@
+extern crate cgmath;
+
+use cgmath::InnerSpace;
+
+use sphere_grid::edge::Edge;
+use sphere_grid::grid::Grid;
+use sphere_grid::tile::GridTile;
+
+/* Lateral heat exchange between neighbouring tiles. The heat through a shared edge is
+   diffusivity * edge length / center distance * temperature difference * the harmonic mean of both surface heat
+   capacities, so energy is conserved and a tile changes temperature in inverse proportion to its own capacity */
+#[derive(Copy, Clone, Debug)]
+pub struct HeatDiffusion {
+    pub atmosphere_diffusivity: f64, // m^2/s, between any two tiles
+    pub ocean_diffusivity: f64, // m^2/s, added between two water tiles
+    pub iterations: usize,
+}
+
+impl Default for HeatDiffusion {
+    fn default() -> Self {
+        HeatDiffusion {
+            atmosphere_diffusivity: 5.0e5,
+            ocean_diffusivity: 1.0e4,
+            iterations: 8,
+        }
+    }
+}
+
+impl HeatDiffusion {
+    /* Backward Euler solved with Jacobi iterations. Every iteration is a weighted average of the old temperature
+       and the neighbours, so the result stays bounded for any delta_t */
+    pub fn update(&self, grid: &mut Grid, capacities: &[f64], radius: f64, delta_t: f64) {
+        let rates = self.exchange_rates(grid, capacities, radius);
+        let initial: Vec<f64> = grid.tiles.iter().map(|t| t.temperature).collect();
+        let mut current = initial.clone();
+
+        for _ in 0..self.iterations {
+            let mut next = Vec::with_capacity(current.len());
+
+            for i in 0..grid.tiles.len() {
+                let tile = &grid.tiles[i].grid_tile;
+                let mut exchange = 0.0;
+                let mut total_rate = 0.0;
+
+                for k in 0..tile.edge_count as usize {
+                    let rate = rates[i][k] * delta_t;
+                    exchange += rate * current[GridTile::get_id(tile.tiles[k])];
+                    total_rate += rate;
+                }
+
+                next.push((initial[i] + exchange) / (1.0 + total_rate));
+            }
+
+            current = next;
+        }
+
+        for i in 0..grid.tiles.len() {
+            grid.tiles[i].temperature = current[i];
+        }
+    }
+
+    /* 1/s per neighbour, capacities in J / (m^2 K) */
+    fn exchange_rates(&self, grid: &Grid, capacities: &[f64], radius: f64) -> Vec<[f64; 6]> {
+        let mut rates = Vec::with_capacity(grid.tiles.len());
+
+        for i in 0..grid.tiles.len() {
+            let tile = &grid.tiles[i].grid_tile;
+            let area = tile.area() as f64 * radius * radius;
+            let mut tile_rates = [0.0; 6];
+
+            for k in 0..tile.edge_count as usize {
+                let n = GridTile::get_id(tile.tiles[k]);
+
+                let edge_length = Edge::get_length(tile.edges[k]) as f64 * radius;
+                let distance = (GridTile::get_pos(tile.tiles[k]) - tile.pos).magnitude() as f64 * radius;
+
+                let mut diffusivity = self.atmosphere_diffusivity;
+
+                if grid.tiles[i].has_water && grid.tiles[n].has_water {
+                    diffusivity += self.ocean_diffusivity;
+                }
+
+                let capacity = 2.0 * capacities[i] * capacities[n] / (capacities[i] + capacities[n]);
+
+                tile_rates[k] = diffusivity * edge_length / (distance * area) * capacity / capacities[i];
+            }
+
+            rates.push(tile_rates);
+        }
+
+        rates
+    }
+}
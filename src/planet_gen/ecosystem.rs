@@ -0,0 +1,232 @@
+extern crate toml;
+
+use std::fs::File;
+use std::io::Read;
+
+use sphere_grid::grid::Grid;
+use sphere_grid::tile::{ GridTile, PlanetTile };
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize)]
+pub enum Habitat {
+    Land,
+    Water,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Species {
+    pub name: String,
+    pub habitat: Habitat,
+    pub temperature: [f64; 2], // °C
+    pub precipitation: [f64; 2], // kg/m^2/s
+    pub growth_rate: f64, // 1/s
+    pub dispersal_rate: f64, // 1/s
+    pub competitiveness: f64,
+    pub color: [f32; 3],
+}
+
+impl Species {
+    /* 0..1 on the mean climate (PlanetTile::mean_air_temperature, mean_precipitation), highest halfway through the
+       tolerated temperatures, 0 outside them or the habitat */
+    pub fn suitability(&self, tile: &PlanetTile) -> f64 {
+        if tile.has_water != (self.habitat == Habitat::Water) {
+            return 0.0;
+        }
+
+        let [min, max] = self.temperature;
+        let temperature = tile.mean_air_temperature;
+        if temperature <= min || temperature >= max {
+            return 0.0;
+        }
+        let warmth = 1.0 - ((2.0 * temperature - min - max) / (max - min)).powi(2);
+
+        let precipitation = tile.mean_precipitation;
+        if self.habitat == Habitat::Land && (precipitation < self.precipitation[0] || precipitation > self.precipitation[1]) {
+            return 0.0;
+        }
+
+        warmth
+    }
+}
+
+#[derive(Deserialize)]
+struct SpeciesFile {
+    species: Vec<Species>,
+}
+
+/* Populations of several species on every tile, as a share of the tile's capacity. Each grows logistically
+   towards the capacity its climate allows, competing with the others for the room left, spreads to neighbours of
+   its habitat and dies out locally once it declines below extinction_threshold. Newcomers below the threshold are
+   kept while they grow. Species start on their best tile at the first step */
+#[derive(Clone, Debug)]
+pub struct Ecosystem {
+    pub species: Vec<Species>,
+    pub extinction_threshold: f64,
+    pub initial_population: f64,
+
+    populations: Vec<Vec<f64>>, // [species][tile]
+}
+
+impl Default for Ecosystem {
+    fn default() -> Self {
+        Ecosystem::parse(include_str!("../../assets/species.toml"))
+    }
+}
+
+#[allow(dead_code)]
+impl Ecosystem {
+    pub fn load(path: &str) -> Ecosystem {
+        let mut contents = String::new();
+        File::open(path)
+                .expect(&format!("Failed to open {}", path))
+            .read_to_string(&mut contents)
+                .expect(&format!("Failed to read species: {}", path));
+
+        Ecosystem::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Ecosystem {
+        let file: SpeciesFile = toml::from_str(contents).expect("Failed to parse species");
+        Ecosystem::new(file.species)
+    }
+
+    pub fn new(species: Vec<Species>) -> Ecosystem {
+        Ecosystem {
+            species: species,
+            extinction_threshold: 1.0e-4,
+            initial_population: 0.1,
+
+            populations: Vec::new(),
+        }
+    }
+
+    /* Index of the species with the given name, for introduce, range and population */
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.species.iter().position(|s| s.name == name)
+    }
+
+    /* Adds population to a tile, for experiments with invasive species */
+    pub fn introduce(&mut self, grid: &Grid, species: usize, tile: usize, population: f64) {
+        self.ensure_seeded(grid);
+        self.populations[species][tile] += population;
+    }
+
+    pub fn population(&self, species: usize, tile: usize) -> f64 {
+        self.populations.get(species).map_or(0.0, |p| p[tile])
+    }
+
+    /* Tiles the species lives on, empty before the first step */
+    pub fn range(&self, species: usize) -> Vec<bool> {
+        self.populations.get(species).map_or(Vec::new(), |p| p.iter().map(|p| *p > 0.0).collect())
+    }
+
+    /* Share of the planet's surface the species lives on */
+    pub fn range_fraction(&self, grid: &Grid, species: usize) -> f64 {
+        let populations = match self.populations.get(species) {
+            Some(populations) => populations,
+            None => return 0.0,
+        };
+
+        let mut occupied = 0.0;
+        let mut total = 0.0;
+
+        for (t, p) in grid.tiles.iter().zip(populations.iter()) {
+            let area = t.grid_tile.area() as f64;
+            total += area;
+            if *p > 0.0 {
+                occupied += area;
+            }
+        }
+
+        occupied / total
+    }
+
+    /* Species with the largest population on the tile */
+    pub fn dominant(&self, tile: usize) -> Option<usize> {
+        let mut best: Option<(usize, f64)> = None;
+
+        for (s, populations) in self.populations.iter().enumerate() {
+            if populations[tile] > best.map_or(0.0, |b| b.1) {
+                best = Some((s, populations[tile]));
+            }
+        }
+
+        best.map(|b| b.0)
+    }
+
+    pub fn update(&mut self, grid: &Grid, delta_t: f64) {
+        self.ensure_seeded(grid);
+
+        let suitability: Vec<Vec<f64>> = self.species.iter()
+            .map(|s| grid.tiles.iter().map(|t| s.suitability(t)).collect())
+            .collect();
+
+        /* Competition for the room each tile has, weighted by how well each species holds its ground */
+        let crowding: Vec<f64> = (0..grid.tiles.len()).map(|i| {
+            self.species.iter().zip(self.populations.iter()).map(|(s, p)| s.competitiveness * p[i]).sum()
+        }).collect();
+
+        for (s, species) in self.species.iter().enumerate() {
+            let populations = &mut self.populations[s];
+            let mut next = populations.clone();
+
+            for (i, t) in grid.tiles.iter().enumerate() {
+                let population = populations[i];
+                if population == 0.0 {
+                    continue;
+                }
+
+                let capacity = suitability[s][i];
+                let growth = if capacity > 0.0 {
+                    species.growth_rate * population * (1.0 - crowding[i] / (species.competitiveness * capacity))
+                } else {
+                    -species.growth_rate * population
+                };
+                next[i] += growth * delta_t;
+
+                /* Dispersal to neighbours of the same habitat */
+                let tile = &t.grid_tile;
+                let neighbours: Vec<usize> = (0..tile.edge_count as usize)
+                    .map(|k| GridTile::get_id(tile.tiles[k]))
+                    .filter(|n| grid.tiles[*n].has_water == (species.habitat == Habitat::Water))
+                    .collect();
+
+                if !neighbours.is_empty() {
+                    let leaving = population * f64::min(0.5, species.dispersal_rate * delta_t);
+                    next[i] -= leaving;
+
+                    for n in neighbours.iter() {
+                        next[*n] += leaving / neighbours.len() as f64;
+                    }
+                }
+            }
+
+            for (p, previous) in next.iter_mut().zip(populations.iter()) {
+                if *p < self.extinction_threshold && *p < *previous {
+                    *p = 0.0;
+                }
+            }
+
+            *populations = next;
+        }
+    }
+
+    fn ensure_seeded(&mut self, grid: &Grid) {
+        if self.populations.len() != self.species.len() || self.populations.iter().any(|p| p.len() != grid.tiles.len()) {
+            self.seed(grid);
+        }
+    }
+
+    fn seed(&mut self, grid: &Grid) {
+        self.populations = vec![vec![0.0; grid.tiles.len()]; self.species.len()];
+
+        for (s, species) in self.species.iter().enumerate() {
+            let best = (0..grid.tiles.len())
+                .map(|i| (i, species.suitability(&grid.tiles[i])))
+                .fold((0, 0.0), |best, candidate| if candidate.1 > best.1 { candidate } else { best });
+
+            if best.1 > 0.0 {
+                self.populations[s][best.0] = self.initial_population;
+            }
+        }
+    }
+}
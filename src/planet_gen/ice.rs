@@ -0,0 +1,72 @@
+use std::f64;
+
+use sphere_grid::grid::Grid;
+use sphere_grid::tile::PlanetTile;
+
+/* Snow on land and sea ice on water, both tracked as PlanetTile::ice_thickness in metres of ice */
+#[derive(Copy, Clone, Debug)]
+pub struct IceModel {
+    pub freezing_temperature: f64, // °C, snow falls and land ice melts around this temperature
+    pub sea_freezing_temperature: f64, // °C
+
+    pub sea_ice_growth_rate: f64, // m/s per °C below freezing
+    pub melt_rate: f64, // m/s per °C above freezing
+    pub ice_density: f64, // kg/m^3, converts snowfall to ice thickness
+    pub max_thickness: f64, // m
+
+    pub snow_albedo: f64,
+    pub sea_ice_albedo: f64,
+    pub full_cover_thickness: f64, // m, thinner ice only partly covers the tile
+}
+
+impl Default for IceModel {
+    fn default() -> Self {
+        IceModel {
+            freezing_temperature: 0.0,
+            sea_freezing_temperature: -1.8,
+
+            sea_ice_growth_rate: 1.2e-8,
+            melt_rate: 6.0e-8,
+            ice_density: 917.0,
+            max_thickness: 3000.0,
+
+            snow_albedo: 0.8,
+            sea_ice_albedo: 0.6,
+            full_cover_thickness: 0.1,
+        }
+    }
+}
+
+impl IceModel {
+    pub fn update(&self, grid: &mut Grid, delta_t: f64) {
+        for t in &mut grid.tiles {
+            let freezing = if t.has_water { self.sea_freezing_temperature } else { self.freezing_temperature };
+
+            if t.air_temperature < freezing {
+                t.ice_thickness += if t.has_water {
+                    self.sea_ice_growth_rate * (freezing - t.air_temperature) * delta_t
+                } else {
+                    t.precipitation * delta_t / self.ice_density
+                };
+            } else {
+                t.ice_thickness -= self.melt_rate * (t.air_temperature - freezing) * delta_t;
+            }
+
+            t.ice_thickness = t.ice_thickness.max(0.0).min(self.max_thickness);
+        }
+    }
+
+    pub fn coverage(&self, tile: &PlanetTile) -> f64 {
+        f64::min(1.0, tile.ice_thickness / self.full_cover_thickness)
+    }
+
+    /* Blends the material albedo set by MaterialRegistry::assign with snow or sea ice */
+    pub fn apply_albedo(&self, grid: &mut Grid) {
+        for t in &mut grid.tiles {
+            let coverage = self.coverage(t);
+            let ice_albedo = if t.has_water { self.sea_ice_albedo } else { self.snow_albedo };
+
+            t.albedo = (1.0 - coverage) * t.albedo + coverage * ice_albedo;
+        }
+    }
+}
@@ -6,13 +6,57 @@ use cgmath::{ Vector3, Matrix4, InnerSpace, Transform };
 use self::noise::{Fbm, NoiseFn, Point3, Seedable, MultiFractal};
 
 use sphere_grid::grid::Grid;
-use sphere_grid::tile::PlanetCoreMaterial;
+use planet_gen::material::MaterialRegistry;
+use planet_gen::atmosphere::Atmosphere;
+use planet_gen::noise_graph::NoiseGraph;
+
+/* Target hypsometric curve: pairs of the share of the surface lying below a height and that height as
+   a fraction of the way from the min to the max height, both rising from 0 to 1 */
+#[derive(Clone, Debug)]
+pub struct Hypsometry {
+    pub points: Vec<(f64, f64)>,
+}
+
+#[allow(dead_code)]
+impl Hypsometry {
+    /* Earth's over -8000..8000 m, from the deepest trench (0) to the highest peak (1). Wide abyssal plains around
+       -4000 m and continental lowlands, with the sea level at 0 m and 71% of the surface */
+    pub fn earth() -> Hypsometry {
+        Hypsometry {
+            points: vec![
+                (0.0, 0.0),
+                (0.01, 0.1),
+                (0.2, 0.19),
+                (0.5, 0.25),
+                (0.65, 0.37),
+                (0.71, 0.5),
+                (0.85, 0.53),
+                (0.95, 0.59),
+                (0.99, 0.72),
+                (1.0, 1.0),
+            ],
+        }
+    }
+
+    /* Height fraction at the given share of the surface */
+    pub fn height_at(&self, fraction: f64) -> f64 {
+        let i = self.points.iter().position(|p| p.0 >= fraction).unwrap_or(self.points.len() - 1);
+
+        if i == 0 {
+            return self.points[0].1;
+        }
+
+        let (a, b) = (self.points[i - 1], self.points[i]);
+        let t = if b.0 > a.0 { ((fraction - a.0) / (b.0 - a.0)).max(0.0).min(1.0) } else { 1.0 };
+        a.1 + (b.1 - a.1) * t
+    }
+}
 
 pub struct Landscape;
 
 impl Landscape {
-    pub fn fill_heights(grid: &mut Grid, max_height: f64, sea_level: f64) {
-        let perlin_surface = Fbm::default().set_octaves(7);
+    /* Heights in m between min_height and max_height */
+    pub fn fill_heights(grid: &mut Grid, terrain: &NoiseGraph, min_height: f64, max_height: f64, sea_level: f64) {
 
         let perlin_clouds = Fbm::default().set_seed(9043).set_octaves(4);
 
@@ -24,7 +68,7 @@ impl Landscape {
             let pos = tile.grid_tile.pos;
             let point3: Point3<f64> = [pos.x as f64, pos.y as f64, pos.z as f64].into();
             
-            tile.height = perlin_surface.get(point3);
+            tile.height = terrain.get(point3);
 
             tile.has_clouds = perlin_clouds.get(point3) > 0.2;
 
@@ -35,43 +79,174 @@ impl Landscape {
         for i in 0..grid.tiles.len() {
             let tile = &mut grid.tiles[i];
 
-            tile.height = (tile.height - min_noise) / (max_noise - min_noise) * max_height;
-            tile.has_water = tile.height < sea_level;
+            tile.height = min_height + (tile.height - min_noise) / (max_noise - min_noise) * (max_height - min_height);
+        }
+
+        Self::flood(grid, sea_level);
+    }
 
-            if tile.has_water {
-                tile.core_material = &PlanetCoreMaterial::WATER;
+    /* Stretches the heights back between min_height and max_height, after passes such as craters moved them */
+    pub fn normalize_heights(grid: &mut Grid, min_height: f64, max_height: f64) {
+        let min = grid.tiles.iter().fold(f64::MAX, |m, t| f64::min(m, t.height));
+        let max = grid.tiles.iter().fold(f64::MIN, |m, t| f64::max(m, t.height));
+
+        if max > min {
+            for t in &mut grid.tiles {
+                t.height = min_height + (t.height - min) / (max - min) * (max_height - min_height);
             }
         }
     }
 
-    pub fn heat(grid: &mut Grid, model_matrix: Matrix4<f32>, sun_pos: Vector3<f32>, delta_t: f64) {
-        let mut max_temp: f64 = 0.0;
-        let mut min_temp: f64 = 0.0;
+    /* Tiles flooded or drained lose their ice, sea ice doesn't become a glacier or the other way round */
+    pub fn flood(grid: &mut Grid, sea_level: f64) {
         for t in &mut grid.tiles {
-            t.brightness = model_matrix.transform_vector(t.grid_tile.pos).dot(sun_pos);
+            let has_water = t.height < sea_level;
+
+            if has_water != t.has_water {
+                t.has_water = has_water;
+                t.ice_thickness = 0.0;
+            }
+        }
+    }
+
+    /* Moves every height to the one at its area-weighted rank on the target curve, keeping the order of the tiles */
+    pub fn apply_hypsometry(grid: &mut Grid, hypsometry: &Hypsometry, min_height: f64, max_height: f64) {
+        let ranks = Self::area_ranks(grid);
+
+        for (t, rank) in grid.tiles.iter_mut().zip(ranks) {
+            t.height = min_height + hypsometry.height_at(rank) * (max_height - min_height);
+        }
+    }
+
+    /* Height below which the given share of the surface lies, under the lowest tile for no ocean */
+    pub fn sea_level_for_ocean_fraction(grid: &Grid, ocean_fraction: f64) -> f64 {
+        let mut tiles: Vec<(f64, f64)> = grid.tiles.iter().map(|t| (t.height, t.grid_tile.area() as f64)).collect();
+        tiles.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        if ocean_fraction <= 0.0 {
+            return tiles.first().map_or(0.0, |t| t.0 - 1.0);
+        }
+
+        let total_area: f64 = tiles.iter().map(|t| t.1).sum();
+        let mut covered = 0.0;
+
+        for i in 0..tiles.len() {
+            covered += tiles[i].1;
+            if covered / total_area >= ocean_fraction {
+                /* Halfway to the next tile so this one is just under water */
+                return if i + 1 < tiles.len() { 0.5 * (tiles[i].0 + tiles[i + 1].0) } else { tiles[i].0 + 1.0 };
+            }
+        }
 
-            let stephen_bolzman_const = 5.67036713 * 0.00000001;
+        tiles.last().map_or(0.0, |t| t.0 + 1.0)
+    }
 
-            let q_sun = 1000.0; // W ~ J / s
-            let q_absorbed: f64;
+    /* Share of the surface above the sea */
+    pub fn land_fraction(grid: &Grid) -> f64 {
+        let mut land = 0.0;
+        let mut total = 0.0;
 
-            if t.brightness <= 0.0 {
-                q_absorbed = 0.0;
-            } else {
-                q_absorbed = q_sun * t.core_material.emissivity() * t.brightness as f64;
+        for t in &grid.tiles {
+            let area = t.grid_tile.area() as f64;
+            total += area;
+            if !t.has_water {
+                land += area;
             }
+        }
 
-            let q_emitted = (273.0 + t.temperature).powf(4.0) * t.core_material.emissivity() * stephen_bolzman_const;
-            let delta_q = (q_absorbed - q_emitted) * delta_t; // J
+        land / total
+    }
 
-            let delta_temperature = delta_q / (t.core_material.density() * t.core_material.specific_heat());
+    /* Share of the surface below each tile's centre, by area */
+    fn area_ranks(grid: &Grid) -> Vec<f64> {
+        let mut order: Vec<usize> = (0..grid.tiles.len()).collect();
+        order.sort_by(|a, b| grid.tiles[*a].height.partial_cmp(&grid.tiles[*b].height).unwrap());
 
-            t.temperature = t.temperature + delta_temperature;
+        let total_area: f64 = grid.tiles.iter().map(|t| t.grid_tile.area() as f64).sum();
+        let mut ranks = vec![0.0; grid.tiles.len()];
+        let mut below = 0.0;
 
-            max_temp = f64::max(max_temp, t.temperature);
-            min_temp = f64::min(min_temp, t.temperature);
+        for i in order {
+            let area = grid.tiles[i].grid_tile.area() as f64;
+            ranks[i] = (below + 0.5 * area) / total_area;
+            below += area;
         }
 
-        println!("Max temp: {:?}    Min Temp: {:?}", max_temp, min_temp);
+        ranks
+    }
+
+    pub const STEFAN_BOLTZMANN: f64 = 5.670374419e-8; // W / (m^2 K^4)
+
+    /* solar_flux is the stellar flux (W/m^2) at the planet, sun_direction the unit vector towards the star.
+       Sets the net flux into the ground and how fast it changes with the surface temperature, Subsurface::update
+       turns them into temperature */
+    pub fn heat(grid: &mut Grid, materials: &MaterialRegistry, atmosphere: &Atmosphere, model_matrix: Matrix4<f32>, sun_direction: Vector3<f32>, solar_flux: f64, delta_t: f64) -> EnergyBalance {
+        let mut balance = EnergyBalance::default();
+        let mut total_area = 0.0;
+
+        for t in &mut grid.tiles {
+            let normal = model_matrix.transform_vector(t.grid_tile.pos).normalize();
+            t.brightness = f32::max(0.0, normal.dot(sun_direction));
+
+            let material = materials.get(t.material);
+
+            t.insolation = solar_flux * t.brightness as f64;
+
+            let transmitted = 1.0 - atmosphere.shortwave_reflection - atmosphere.shortwave_absorption;
+
+            let q_air = t.insolation * atmosphere.shortwave_absorption; // W / m^2
+            let q_surface = t.insolation * transmitted * (1.0 - t.albedo);
+            let (q_longwave, q_emitted) = atmosphere.exchange(t, material.emissivity, q_air, delta_t);
+
+            t.surface_flux = q_surface + q_longwave; // W / m^2
+            t.surface_coupling = atmosphere.surface_coupling(material.emissivity, t.temperature);
+
+            let area = t.grid_tile.area() as f64;
+            balance.incoming += t.insolation * area;
+            balance.absorbed += (q_air + q_surface) * area;
+            balance.emitted += q_emitted * area;
+            balance.surface_emitted += material.emissivity * (273.15 + t.temperature).powf(4.0) * Landscape::STEFAN_BOLTZMANN * area;
+            balance.mean_temperature += t.temperature * area;
+            total_area += area;
+        }
+
+        balance.incoming /= total_area;
+        balance.absorbed /= total_area;
+        balance.emitted /= total_area;
+        balance.surface_emitted /= total_area;
+        balance.mean_temperature /= total_area;
+
+        balance
+    }
+}
+
+/* Global means over the surface, fluxes in W/m^2, temperature in °C. Emitted is what leaves to space */
+#[derive(Copy, Clone, Default, Debug)]
+pub struct EnergyBalance {
+    pub incoming: f64,
+    pub absorbed: f64,
+    pub emitted: f64,
+    pub surface_emitted: f64,
+    pub mean_temperature: f64,
+}
+
+#[allow(dead_code)]
+impl EnergyBalance {
+    pub fn net(&self) -> f64 {
+        self.absorbed - self.emitted
+    }
+
+    pub fn bond_albedo(&self) -> f64 {
+        if self.incoming > 0.0 { 1.0 - self.absorbed / self.incoming } else { 0.0 }
+    }
+
+    /* Longwave radiation of the surface held back by the atmosphere */
+    pub fn greenhouse_effect(&self) -> f64 {
+        self.surface_emitted - self.emitted
+    }
+
+    /* Temperature (°C) at which a black body would emit what the planet absorbs */
+    pub fn equilibrium_temperature(&self) -> f64 {
+        (self.absorbed / Landscape::STEFAN_BOLTZMANN).powf(0.25) - 273.15
     }
-}
\ No newline at end of file
+}
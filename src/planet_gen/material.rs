@@ -0,0 +1,118 @@
+extern crate toml;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+
+use sphere_grid::grid::Grid;
+use planet_gen::biome::Biome;
+
+pub type MaterialId = usize;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Material {
+    pub name: String,
+    pub albedo: f64,
+    pub emissivity: f64,
+    pub specific_heat: f64, // J / (kg K)
+    pub density: f64, // kg / m^3
+    pub conductivity: f64, // W / (m K)
+    pub depth: f64, // m, active layer
+
+    #[serde(default)]
+    pub biomes: Vec<Biome>,
+    pub above_elevation: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct MaterialFile {
+    material: Vec<Material>,
+}
+
+/* Materials are referenced from tiles by their index in the registry */
+#[derive(Clone, Debug)]
+pub struct MaterialRegistry {
+    materials: Vec<Material>,
+    biome_materials: HashMap<Biome, MaterialId>,
+}
+
+impl Default for MaterialRegistry {
+    fn default() -> Self {
+        MaterialRegistry::parse(include_str!("../../assets/materials.toml"))
+    }
+}
+
+#[allow(dead_code)]
+impl MaterialRegistry {
+    pub fn load(path: &str) -> MaterialRegistry {
+        let mut contents = String::new();
+        File::open(path)
+                .expect(&format!("Failed to open {}", path))
+            .read_to_string(&mut contents)
+                .expect(&format!("Failed to read materials: {}", path));
+
+        MaterialRegistry::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> MaterialRegistry {
+        let file: MaterialFile = toml::from_str(contents).expect("Failed to parse materials");
+        MaterialRegistry::new(file.material)
+    }
+
+    pub fn new(materials: Vec<Material>) -> MaterialRegistry {
+        assert!(!materials.is_empty(), "Material registry needs at least one material");
+
+        let mut biome_materials = HashMap::new();
+
+        for (id, material) in materials.iter().enumerate() {
+            for biome in material.biomes.iter() {
+                biome_materials.insert(*biome, id);
+            }
+        }
+
+        MaterialRegistry {
+            materials: materials,
+            biome_materials: biome_materials,
+        }
+    }
+
+    pub fn get(&self, id: MaterialId) -> &Material {
+        &self.materials[id]
+    }
+
+    pub fn find(&self, name: &str) -> Option<MaterialId> {
+        self.materials.iter().position(|m| m.name == name)
+    }
+
+    pub fn materials(&self) -> &[Material] {
+        &self.materials
+    }
+
+    /* Elevation rules win over biomes, the highest matching threshold is used */
+    pub fn material_for(&self, biome: Biome, elevation: f64, has_water: bool) -> MaterialId {
+        if !has_water {
+            let mut best: Option<(MaterialId, f64)> = None;
+
+            for (id, material) in self.materials.iter().enumerate() {
+                if let Some(threshold) = material.above_elevation {
+                    if elevation > threshold && best.map_or(true, |(_, b)| threshold > b) {
+                        best = Some((id, threshold));
+                    }
+                }
+            }
+
+            if let Some((id, _)) = best {
+                return id;
+            }
+        }
+
+        *self.biome_materials.get(&biome).unwrap_or(&0)
+    }
+
+    pub fn assign(&self, grid: &mut Grid, sea_level: f64) {
+        for t in &mut grid.tiles {
+            t.material = self.material_for(t.biome, t.height - sea_level, t.has_water);
+            t.albedo = self.materials[t.material].albedo;
+        }
+    }
+}
@@ -1,5 +1,29 @@
 pub mod planet;
+pub mod plates;
 pub mod landscape;
+pub mod material;
+pub mod atmosphere;
+pub mod biome;
+pub mod circulation;
+pub mod climate;
+pub mod clock;
+pub mod craters;
+pub mod diffusion;
+pub mod ecosystem;
+pub mod ice;
+pub mod moisture;
+pub mod noise_graph;
+pub mod ocean;
+pub mod orbit;
+pub mod preset;
+pub mod region;
+pub mod resources;
+pub mod rotation;
+pub mod settlement;
+pub mod star;
+pub mod subsurface;
+pub mod transport;
+pub mod vegetation;
+pub mod volcanism;
 
 pub use self::planet::Planet;
-pub use self::landscape::Landscape;
\ No newline at end of file
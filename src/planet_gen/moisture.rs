@@ -0,0 +1,121 @@
+extern crate cgmath;
+
+use std::f64;
+use cgmath::{ Vector3, InnerSpace };
+
+use sphere_grid::grid::Grid;
+use sphere_grid::tile::GridTile;
+
+use planet_gen::transport::Transport;
+
+/* Column water vapour model. Moisture is precipitable water in kg/m^2, precipitation is in kg/m^2/s (mm/s) */
+#[derive(Copy, Clone, Debug)]
+pub struct MoistureModel {
+    pub saturation_at_freezing: f64, // kg/m^2 of vapour an air column holds at 0 °C
+    pub saturation_growth: f64, // Clausius-Clapeyron, relative growth per °C
+
+    pub evaporation_rate: f64, // 1/s, fraction of the saturation deficit evaporated from water
+    pub condensation_rate: f64, // 1/s, fraction of the excess over cloud_humidity that rains out
+    pub orographic_efficiency: f64,
+    pub vapour_scale_height: f64, // m
+    pub descent_warming: f64, // °C per m of descent that sinking air warms above the ground temperature
+
+    pub cloud_humidity: f64,
+}
+
+impl Default for MoistureModel {
+    fn default() -> Self {
+        MoistureModel {
+            saturation_at_freezing: 10.0,
+            saturation_growth: 0.07,
+
+            evaporation_rate: 3.0e-6,
+            condensation_rate: 2.0e-5,
+            orographic_efficiency: 1.0,
+            vapour_scale_height: 2000.0,
+            descent_warming: 0.0035,
+
+            cloud_humidity: 0.8,
+        }
+    }
+}
+
+impl MoistureModel {
+    pub fn saturation(&self, temperature: f64) -> f64 {
+        self.saturation_at_freezing * (self.saturation_growth * f64::min(temperature, 60.0)).exp()
+    }
+
+    pub fn update(&self, grid: &mut Grid, sea_level: f64, radius: f64, delta_t: f64) {
+        let winds: Vec<Vector3<f32>> = grid.tiles.iter().map(|t| t.wind).collect();
+
+        for t in &mut grid.tiles {
+            if t.has_water {
+                let deficit = f64::max(0.0, self.saturation(t.temperature) - t.moisture);
+                t.moisture += deficit * f64::min(1.0, self.evaporation_rate * delta_t);
+            }
+        }
+
+        let moisture: Vec<f64> = grid.tiles.iter().map(|t| t.moisture).collect();
+        let moisture = Transport::advect(grid, &moisture, &winds, radius, delta_t);
+
+        for i in 0..grid.tiles.len() {
+            let uplift = Self::uplift(grid, i, winds[i], sea_level, radius);
+            let descent = Self::descent(grid, i, winds[i], sea_level);
+
+            let t = &mut grid.tiles[i];
+            t.moisture = moisture[i];
+
+            /* Air forced up a slope cools and loses vapour, air coming down one warms, holding more vapour before it
+               rains and looking drier (rain shadow) */
+            let saturation = self.saturation(t.air_temperature + self.descent_warming * descent);
+
+            let orographic = self.orographic_efficiency * f64::max(0.0, uplift) / self.vapour_scale_height;
+            let excess = f64::max(0.0, t.moisture - self.cloud_humidity * saturation);
+
+            let rain = f64::min(t.moisture, (self.condensation_rate * excess + orographic * t.moisture) * delta_t);
+
+            t.moisture -= rain;
+            t.precipitation = rain / delta_t;
+            t.humidity = f64::min(1.0, t.moisture / saturation);
+            t.has_clouds = t.humidity > self.cloud_humidity || (rain > 0.0 && orographic > 0.0);
+        }
+    }
+
+    /* m the air blown onto tile i has come down from its upwind neighbours */
+    fn descent(grid: &Grid, i: usize, wind: Vector3<f32>, sea_level: f64) -> f64 {
+        let tile = &grid.tiles[i];
+        let surface = f64::max(tile.height, sea_level);
+        let mut descent = 0.0;
+        let mut total_weight = 0.0;
+
+        for k in 0..tile.grid_tile.edge_count as usize {
+            let n = GridTile::get_id(tile.grid_tile.tiles[k]);
+            let offset = grid.tiles[n].grid_tile.pos - tile.grid_tile.pos;
+
+            let weight = f64::max(0.0, -wind.dot(offset.normalize()) as f64);
+            descent += weight * (f64::max(grid.tiles[n].height, sea_level) - surface);
+            total_weight += weight;
+        }
+
+        if total_weight > 0.0 { f64::max(0.0, descent / total_weight) } else { 0.0 }
+    }
+
+    /* Vertical speed (m/s) of air blown over the terrain around tile i */
+    fn uplift(grid: &Grid, i: usize, wind: Vector3<f32>, sea_level: f64, radius: f64) -> f64 {
+        let tile = &grid.tiles[i];
+        let surface = f64::max(tile.height, sea_level);
+        let mut uplift = 0.0;
+
+        for k in 0..tile.grid_tile.edge_count as usize {
+            let n = GridTile::get_id(tile.grid_tile.tiles[k]);
+            let offset = grid.tiles[n].grid_tile.pos - tile.grid_tile.pos;
+            let distance = offset.magnitude() as f64 * radius;
+
+            let slope = (f64::max(grid.tiles[n].height, sea_level) - surface) / distance;
+
+            uplift += f64::max(0.0, wind.dot(offset.normalize()) as f64) * slope;
+        }
+
+        uplift
+    }
+}
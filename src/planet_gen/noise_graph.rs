@@ -0,0 +1,262 @@
+extern crate noise;
+extern crate toml;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+
+use self::noise::{ Billow, Blend, Fbm, MultiFractal, NoiseFn, Perlin, Point3, RidgedMulti, Seedable, Select, Terrace };
+
+/* Node as written in a terrain file, see assets/terrain.toml. Sources are names of nodes listed before it */
+#[derive(Deserialize)]
+struct NodeEntry {
+    name: String,
+    #[serde(flatten)]
+    node: NodeSpec,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum NodeSpec {
+    Perlin(FractalSpec),
+    Fbm(FractalSpec),
+    Ridged(FractalSpec),
+    Billow(FractalSpec),
+    Warp { source: String, #[serde(default)] seed: u32, frequency: f64, strength: f64 },
+    Select { sources: [String; 2], control: String, lower: f64, upper: f64, #[serde(default)] falloff: f64 },
+    Blend { sources: [String; 2], control: String },
+    Terrace { source: String, control_points: Vec<f64>, #[serde(default)] invert: bool },
+    ScaleBias { source: String, #[serde(default = "one")] scale: f64, #[serde(default)] bias: f64 },
+    Add { sources: [String; 2] },
+    Multiply { sources: [String; 2] },
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+struct FractalSpec {
+    seed: u32,
+    octaves: usize,
+    frequency: f64,
+    lacunarity: f64,
+    persistence: f64,
+}
+
+impl Default for FractalSpec {
+    fn default() -> Self {
+        FractalSpec {
+            seed: 0,
+            octaves: 6,
+            frequency: 1.0,
+            lacunarity: 2.0,
+            persistence: 0.5,
+        }
+    }
+}
+
+fn one() -> f64 {
+    1.0
+}
+
+#[derive(Deserialize)]
+struct GraphFile {
+    output: String,
+    node: Vec<NodeEntry>,
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Perlin(Perlin),
+    Fbm(Fbm),
+    Ridged(RidgedMulti),
+    Billow(Billow),
+    Warp { source: usize, offsets: [Fbm; 3], strength: f64 },
+    Select { sources: [usize; 2], control: usize, lower: f64, upper: f64, falloff: f64 },
+    Blend { sources: [usize; 2], control: usize },
+    Terrace { source: usize, control_points: Vec<f64>, invert: bool },
+    ScaleBias { source: usize, scale: f64, bias: f64 },
+    Add { sources: [usize; 2] },
+    Multiply { sources: [usize; 2] },
+}
+
+/* Terrain heights as a graph of noise functions loaded from a data file. Nodes only read nodes listed before
+   them, so the graph can't have cycles. Select, blend and terrace use the noise crate modules, warp moves
+   the sample point by three fractal noises before reading its source */
+#[derive(Clone, Debug)]
+pub struct NoiseGraph {
+    nodes: Vec<Node>,
+    names: Vec<String>,
+    output: usize,
+}
+
+impl Default for NoiseGraph {
+    fn default() -> Self {
+        NoiseGraph::parse(include_str!("../../assets/terrain.toml"))
+    }
+}
+
+/* Lets the noise crate modules read another node of the graph */
+struct NodeRef<'a> {
+    graph: &'a NoiseGraph,
+    id: usize,
+}
+
+impl<'a> NoiseFn<Point3<f64>> for NodeRef<'a> {
+    fn get(&self, point: Point3<f64>) -> f64 {
+        self.graph.eval(self.id, point)
+    }
+}
+
+#[allow(dead_code)]
+impl NoiseGraph {
+    pub fn load(path: &str) -> NoiseGraph {
+        let mut contents = String::new();
+        File::open(path)
+                .expect(&format!("Failed to open {}", path))
+            .read_to_string(&mut contents)
+                .expect(&format!("Failed to read terrain: {}", path));
+
+        NoiseGraph::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> NoiseGraph {
+        let GraphFile { output, node: entries } = toml::from_str(contents).expect("Failed to parse terrain");
+
+        let mut ids: HashMap<String, usize> = HashMap::new();
+        let mut nodes = Vec::with_capacity(entries.len());
+        let mut names = Vec::with_capacity(entries.len());
+
+        for NodeEntry { name, node } in entries {
+            let node = {
+                let find = |source: &str| -> usize {
+                    *ids.get(source).unwrap_or_else(|| panic!("Terrain node {} reads {}, which isn't defined before it", name, source))
+                };
+
+                match node {
+                    NodeSpec::Perlin(spec) => Node::Perlin(Perlin::new().set_seed(spec.seed)),
+                    NodeSpec::Fbm(spec) => Node::Fbm(Self::fractal(Fbm::new(), &spec)),
+                    NodeSpec::Ridged(spec) => Node::Ridged(Self::fractal(RidgedMulti::new(), &spec)),
+                    NodeSpec::Billow(spec) => Node::Billow(Self::fractal(Billow::new(), &spec)),
+                    NodeSpec::Warp { source, seed, frequency, strength } => Node::Warp {
+                        source: find(&source),
+                        offsets: [
+                            Fbm::new().set_seed(seed).set_octaves(4).set_frequency(frequency),
+                            Fbm::new().set_seed(seed.wrapping_add(1)).set_octaves(4).set_frequency(frequency),
+                            Fbm::new().set_seed(seed.wrapping_add(2)).set_octaves(4).set_frequency(frequency),
+                        ],
+                        strength: strength,
+                    },
+                    NodeSpec::Select { sources, control, lower, upper, falloff } => Node::Select {
+                        sources: [find(&sources[0]), find(&sources[1])],
+                        control: find(&control),
+                        lower: lower,
+                        upper: upper,
+                        falloff: falloff,
+                    },
+                    NodeSpec::Blend { sources, control } => Node::Blend {
+                        sources: [find(&sources[0]), find(&sources[1])],
+                        control: find(&control),
+                    },
+                    NodeSpec::Terrace { source, control_points, invert } => {
+                        assert!(control_points.len() >= 2, "Terrace node {} needs at least two control points", name);
+                        Node::Terrace { source: find(&source), control_points: control_points, invert: invert }
+                    },
+                    NodeSpec::ScaleBias { source, scale, bias } => Node::ScaleBias { source: find(&source), scale: scale, bias: bias },
+                    NodeSpec::Add { sources } => Node::Add { sources: [find(&sources[0]), find(&sources[1])] },
+                    NodeSpec::Multiply { sources } => Node::Multiply { sources: [find(&sources[0]), find(&sources[1])] },
+                }
+            };
+
+            ids.insert(name.clone(), nodes.len());
+            nodes.push(node);
+            names.push(name);
+        }
+
+        let output = *ids.get(&output).unwrap_or_else(|| panic!("Terrain output {} isn't defined", output));
+
+        NoiseGraph {
+            nodes: nodes,
+            names: names,
+            output: output,
+        }
+    }
+
+    /* Same graph with every seed moved by offset, for another planet of the same style */
+    pub fn reseed(&self, offset: u32) -> NoiseGraph {
+        let mut graph = self.clone();
+
+        for node in &mut graph.nodes {
+            match *node {
+                Node::Perlin(ref mut n) => *n = n.set_seed(n.seed().wrapping_add(offset)),
+                Node::Fbm(ref mut n) => *n = n.clone().set_seed(n.seed().wrapping_add(offset)),
+                Node::Ridged(ref mut n) => *n = n.clone().set_seed(n.seed().wrapping_add(offset)),
+                Node::Billow(ref mut n) => *n = n.clone().set_seed(n.seed().wrapping_add(offset)),
+                Node::Warp { ref mut offsets, .. } => {
+                    for n in offsets.iter_mut() {
+                        *n = n.clone().set_seed(n.seed().wrapping_add(offset));
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        graph
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn get(&self, point: Point3<f64>) -> f64 {
+        self.eval(self.output, point)
+    }
+
+    /* Value of a single node, to look at parts of the graph */
+    pub fn get_node(&self, name: &str, point: Point3<f64>) -> Option<f64> {
+        self.names.iter().position(|n| n == name).map(|id| self.eval(id, point))
+    }
+
+    fn eval(&self, id: usize, point: Point3<f64>) -> f64 {
+        let node = |id| NodeRef { graph: self, id: id };
+
+        match self.nodes[id] {
+            Node::Perlin(ref n) => n.get(point),
+            Node::Fbm(ref n) => n.get(point),
+            Node::Ridged(ref n) => n.get(point),
+            Node::Billow(ref n) => n.get(point),
+            Node::Warp { source, ref offsets, strength } => {
+                let warped = [
+                    point[0] + strength * offsets[0].get(point),
+                    point[1] + strength * offsets[1].get(point),
+                    point[2] + strength * offsets[2].get(point),
+                ];
+                self.eval(source, warped)
+            },
+            Node::Select { sources, control, lower, upper, falloff } => {
+                let (a, b, c) = (node(sources[0]), node(sources[1]), node(control));
+                Select::new(&a, &b, &c).set_bounds(lower, upper).set_falloff(falloff).get(point)
+            },
+            Node::Blend { sources, control } => {
+                let (a, b, c) = (node(sources[0]), node(sources[1]), node(control));
+                Blend::new(&a, &b, &c).get(point)
+            },
+            Node::Terrace { source, ref control_points, invert } => {
+                let s = node(source);
+                control_points.iter()
+                    .fold(Terrace::new(&s), |terrace, p| terrace.add_control_point(*p))
+                    .invert_terraces(invert)
+                    .get(point)
+            },
+            Node::ScaleBias { source, scale, bias } => self.eval(source, point) * scale + bias,
+            Node::Add { sources } => self.eval(sources[0], point) + self.eval(sources[1], point),
+            Node::Multiply { sources } => self.eval(sources[0], point) * self.eval(sources[1], point),
+        }
+    }
+
+    fn fractal<T: MultiFractal + Seedable>(noise: T, spec: &FractalSpec) -> T {
+        noise.set_seed(spec.seed)
+            .set_octaves(spec.octaves)
+            .set_frequency(spec.frequency)
+            .set_lacunarity(spec.lacunarity)
+            .set_persistence(spec.persistence)
+    }
+}
@@ -0,0 +1,161 @@
+extern crate cgmath;
+
+use std::f64;
+use cgmath::{ Vector3, InnerSpace, Zero };
+
+use sphere_grid::grid::Grid;
+use sphere_grid::tile::GridTile;
+
+use planet_gen::circulation::Circulation;
+use planet_gen::transport::Transport;
+
+/* Wind driven surface currents. Currents are tangent vectors in m/s, zero on land */
+#[derive(Copy, Clone, Debug)]
+pub struct OceanCurrents {
+    pub wind_drag: f64, // fraction of the wind speed passed to the surface water
+    pub ekman_angle: f64, // degrees, deflection from the wind direction by the Coriolis effect
+    pub smoothing_passes: usize, // relaxation passes spreading the flow along coasts into basin-wide currents
+
+    pub heat_transport: f64, // fraction of the current speed at which water carries temperature
+    pub upwelling_efficiency: f64,
+    pub deep_water_temperature: f64, // °C
+}
+
+impl Default for OceanCurrents {
+    fn default() -> Self {
+        OceanCurrents {
+            wind_drag: 0.03,
+            ekman_angle: 45.0,
+            smoothing_passes: 4,
+
+            heat_transport: 1.0,
+            upwelling_efficiency: 1.0,
+            deep_water_temperature: 4.0,
+        }
+    }
+}
+
+impl OceanCurrents {
+    pub fn update(&self, grid: &mut Grid, circulation: &Circulation, radius: f64, delta_t: f64) {
+        let mut currents: Vec<Vector3<f32>> = grid.tiles.iter().map(|t| {
+            if t.has_water { self.wind_driven_current(t.wind, t.grid_tile.pos, circulation) } else { Vector3::zero() }
+        }).collect();
+
+        for i in 0..grid.tiles.len() {
+            currents[i] = Self::deflect_by_coast(grid, i, currents[i]);
+        }
+
+        for _ in 0..self.smoothing_passes {
+            currents = Self::smooth(grid, &currents);
+        }
+
+        for i in 0..grid.tiles.len() {
+            grid.tiles[i].current = currents[i];
+        }
+
+        self.transport_heat(grid, &currents, radius, delta_t);
+    }
+
+    /* Ekman transport: to the right of the wind when the Coriolis parameter is positive, to the left otherwise */
+    fn wind_driven_current(&self, wind: Vector3<f32>, pos: Vector3<f32>, circulation: &Circulation) -> Vector3<f32> {
+        let angle = -self.ekman_angle.to_radians() * circulation.coriolis_parameter(pos).signum();
+        let deflected = wind * angle.cos() as f32 + pos.cross(wind) * angle.sin() as f32;
+
+        deflected * self.wind_drag as f32
+    }
+
+    /* Water can't flow into land, the part of the current heading into the coast is turned along it */
+    fn deflect_by_coast(grid: &Grid, i: usize, current: Vector3<f32>) -> Vector3<f32> {
+        let tile = &grid.tiles[i].grid_tile;
+        let speed = current.magnitude();
+        let mut current = current;
+
+        if speed == 0.0 {
+            return current;
+        }
+
+        for k in 0..tile.edge_count as usize {
+            if grid.tiles[GridTile::get_id(tile.tiles[k])].has_water {
+                continue;
+            }
+
+            let direction = (GridTile::get_pos(tile.tiles[k]) - tile.pos).normalize();
+            let into_coast = current.dot(direction);
+
+            if into_coast > 0.0 {
+                current -= direction * into_coast;
+            }
+        }
+
+        if current.magnitude2() > 0.0 { current.normalize_to(speed) } else { current }
+    }
+
+    fn smooth(grid: &Grid, currents: &[Vector3<f32>]) -> Vec<Vector3<f32>> {
+        let mut result = currents.to_vec();
+
+        for i in 0..grid.tiles.len() {
+            if !grid.tiles[i].has_water {
+                continue;
+            }
+
+            let tile = &grid.tiles[i].grid_tile;
+            let mut sum = currents[i];
+            let mut count = 1.0;
+
+            for k in 0..tile.edge_count as usize {
+                let n = GridTile::get_id(tile.tiles[k]);
+
+                if grid.tiles[n].has_water {
+                    sum += currents[n];
+                    count += 1.0;
+                }
+            }
+
+            /* Project back onto the tangent plane, neighbours' tangent planes differ slightly */
+            let average = sum / count;
+            let average = average - tile.pos * average.dot(tile.pos);
+
+            result[i] = Self::deflect_by_coast(grid, i, average);
+        }
+
+        result
+    }
+
+    fn transport_heat(&self, grid: &mut Grid, currents: &[Vector3<f32>], radius: f64, delta_t: f64) {
+        let velocities: Vec<Vector3<f32>> = currents.iter().map(|c| c * self.heat_transport as f32).collect();
+        let temperatures: Vec<f64> = grid.tiles.iter().map(|t| t.temperature).collect();
+
+        let advected = {
+            let tiles = &grid.tiles;
+            Transport::advect_scalar_where(grid, &temperatures, &velocities, radius, delta_t, |i| tiles[i].has_water)
+        };
+
+        for i in 0..grid.tiles.len() {
+            if !grid.tiles[i].has_water {
+                continue;
+            }
+
+            /* Surface water moving apart is replaced by cold water from below (coastal and equatorial upwelling) */
+            let divergence = Self::divergence(grid, i, currents, radius);
+            let upwelling = f64::min(1.0, self.upwelling_efficiency * f64::max(0.0, divergence) * delta_t);
+
+            let t = &mut grid.tiles[i];
+            t.temperature = advected[i] + upwelling * (self.deep_water_temperature - advected[i]);
+        }
+    }
+
+    fn divergence(grid: &Grid, i: usize, currents: &[Vector3<f32>], radius: f64) -> f64 {
+        let tile = &grid.tiles[i].grid_tile;
+        let mut divergence = 0.0;
+
+        for k in 0..tile.edge_count as usize {
+            let offset = GridTile::get_pos(tile.tiles[k]) - tile.pos;
+            let distance = offset.magnitude() as f64 * radius;
+            let n = GridTile::get_id(tile.tiles[k]);
+
+            divergence += (currents[n] - currents[i]).dot(offset.normalize()) as f64 / distance;
+        }
+
+        divergence * 2.0 / tile.edge_count as f64
+    }
+}
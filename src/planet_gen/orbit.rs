@@ -0,0 +1,129 @@
+extern crate cgmath;
+
+use std::f64;
+use std::f64::consts::PI;
+use cgmath::Vector3;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    pub fn opposite(&self) -> Season {
+        match self {
+            Season::Spring => Season::Autumn,
+            Season::Summer => Season::Winter,
+            Season::Autumn => Season::Spring,
+            Season::Winter => Season::Summer,
+        }
+    }
+}
+
+/* Keplerian orbit around the star. Simulation time 0 is the northern spring equinox.
+   World frame: the orbit lies in the XZ plane and turns anticlockwise about +Y. The spin axis is +Y tilted towards
+   -Z by axial_tilt, and prograde spin is a positive angle about +Y (Rotation::angle). So everything in the body
+   frame turns about +Y too: east is +Y x pos (Transport::local_basis), the Coriolis parameter is positive in the
+   north and the sun rises in the east */
+#[derive(Copy, Clone, Debug)]
+pub struct Orbit {
+    pub semi_major_axis: f64, // m
+    pub eccentricity: f64,
+    pub year_length: f64, // s
+    pub axial_tilt: f64, // degrees
+    pub perihelion_longitude: f64, // degrees, solar longitude at perihelion
+}
+
+impl Default for Orbit {
+    fn default() -> Self {
+        Orbit {
+            semi_major_axis: Orbit::ASTRONOMICAL_UNIT,
+            eccentricity: 0.0167,
+            year_length: 365.25 * 24.0 * 60.0 * 60.0,
+            axial_tilt: 23.44,
+            perihelion_longitude: 283.0,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl Orbit {
+    pub const ASTRONOMICAL_UNIT: f64 = 1.495978707e11; // m
+
+    pub fn mean_motion(&self) -> f64 {
+        2.0 * PI / self.year_length
+    }
+
+    /* Angle of the sun along the ecliptic as seen from the planet, 0 at the northern spring equinox */
+    pub fn solar_longitude(&self, time: f64) -> f64 {
+        let perihelion = self.perihelion_longitude.to_radians();
+        let true_anomaly = Self::true_anomaly(self.eccentric_anomaly(time), self.eccentricity);
+
+        (true_anomaly + perihelion).rem_euclid(2.0 * PI)
+    }
+
+    pub fn distance(&self, time: f64) -> f64 {
+        self.semi_major_axis * (1.0 - self.eccentricity * self.eccentric_anomaly(time).cos())
+    }
+
+    pub fn subsolar_latitude(&self, time: f64) -> f64 {
+        (self.axial_tilt.to_radians().sin() * self.solar_longitude(time).sin()).asin()
+    }
+
+    /* Unit vector from the planet towards the star in the world frame */
+    pub fn sun_direction(&self, time: f64) -> Vector3<f32> {
+        let longitude = self.solar_longitude(time);
+        Vector3::new(longitude.cos() as f32, 0.0, -longitude.sin() as f32)
+    }
+
+    pub fn day_of_year(&self, time: f64, day_length: f64) -> u32 {
+        (time.rem_euclid(self.year_length) / day_length).floor() as u32 + 1
+    }
+
+    /* Astronomical seasons, the southern hemisphere has the opposite one */
+    pub fn season(&self, time: f64, northern: bool) -> Season {
+        let quarter = (self.solar_longitude(time) / (0.5 * PI)).floor() as u32;
+
+        let season = match quarter {
+            0 => Season::Spring,
+            1 => Season::Summer,
+            2 => Season::Autumn,
+            _ => Season::Winter,
+        };
+
+        if northern { season } else { season.opposite() }
+    }
+
+    fn eccentric_anomaly(&self, time: f64) -> f64 {
+        let e = self.eccentricity;
+
+        /* Mean anomaly at the equinox, where the true anomaly is minus the perihelion longitude */
+        let equinox_true_anomaly = -self.perihelion_longitude.to_radians();
+        let equinox_eccentric_anomaly = 2.0 * (((1.0 - e) / (1.0 + e)).sqrt() * (0.5 * equinox_true_anomaly).tan()).atan();
+        let equinox_mean_anomaly = equinox_eccentric_anomaly - e * equinox_eccentric_anomaly.sin();
+
+        let mean_anomaly = (equinox_mean_anomaly + self.mean_motion() * time).rem_euclid(2.0 * PI);
+
+        /* Kepler's equation M = E - e * sin(E), Newton iterations */
+        let mut eccentric_anomaly = if e < 0.8 { mean_anomaly } else { PI };
+
+        for _ in 0..10 {
+            let delta = (eccentric_anomaly - e * eccentric_anomaly.sin() - mean_anomaly) / (1.0 - e * eccentric_anomaly.cos());
+            eccentric_anomaly -= delta;
+
+            if delta.abs() < 1e-12 {
+                break;
+            }
+        }
+
+        eccentric_anomaly
+    }
+
+    fn true_anomaly(eccentric_anomaly: f64, eccentricity: f64) -> f64 {
+        2.0 * ((1.0 + eccentricity).sqrt() * (0.5 * eccentric_anomaly).sin())
+                .atan2((1.0 - eccentricity).sqrt() * (0.5 * eccentric_anomaly).cos())
+    }
+}
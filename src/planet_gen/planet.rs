@@ -4,6 +4,7 @@ extern crate cgmath;
 use glfw::Key;
 
 use self::cgmath::{Deg, Vector2, Vector3, InnerSpace, Zero};
+use std::f32::consts::PI;
 
 use gfx::camera::Camera;
 use gfx::render_target::RenderTarget;
@@ -16,7 +17,29 @@ use sphere_grid::grid::Grid;
 use sphere_grid::grid_mesh::GridMesh;
 use sphere_grid::tile::PlanetTile;
 
-use planet_gen::landscape::Landscape;
+use planet_gen::landscape::{ Landscape, EnergyBalance };
+use planet_gen::atmosphere::Atmosphere;
+use planet_gen::biome::BiomeClassifier;
+use planet_gen::material::MaterialRegistry;
+use planet_gen::moisture::MoistureModel;
+use planet_gen::circulation::Circulation;
+use planet_gen::climate::ClimateMeans;
+use planet_gen::clock::{ SimulationClock, SimulationDate };
+use planet_gen::diffusion::HeatDiffusion;
+use planet_gen::ecosystem::Ecosystem;
+use planet_gen::ice::IceModel;
+use planet_gen::ocean::OceanCurrents;
+use planet_gen::orbit::{ Orbit, Season };
+use planet_gen::plates::Plates;
+use planet_gen::preset::PlanetParams;
+use planet_gen::region::Region;
+use planet_gen::resources::{ Deposits, ResourceModel };
+use planet_gen::rotation::Rotation;
+use planet_gen::settlement::{ SettlementModel, Settlements };
+use planet_gen::star::Star;
+use planet_gen::vegetation::VegetationModel;
+use planet_gen::subsurface::Subsurface;
+use planet_gen::transport::Transport;
 
 use vertex::Vertex;
 
@@ -59,16 +82,28 @@ pub enum PlanetOverlay {
     Heights,
     Brightness,
     Temperature,
+    Biomes,
+    Currents,
+    Ice,
+    Pressure,
+    Vegetation,
+    Species,
+    Settlements,
+    Resources,
 }
 
+#[allow(dead_code)]
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub enum PlanetType {
-    Empty
-}
-
-#[derive(Copy, Clone, PartialEq, Debug)]
-pub enum PlanetMaterial {
-    Granite
+    Empty,
+    EarthLike,
+    Desert,
+    Ocean,
+    Ice,
+    Lava,
+    TidallyLocked,
+    Moon,
+    Mercury,
 }
 
 
@@ -79,10 +114,34 @@ pub struct Planet<'a> {
     surface_target: ShaderTarget<'a, PlanetVertex>,
     atmosphere_target: ShaderTarget<'a, PlanetVertex>,
 
-    sun_pos: Vector3<f32>,
-    rotation: Deg<f32>,
+    pos: Vector3<f32>,
+    radius: f64, // m
+    plates: Plates,
+    rotation: Rotation,
+    rotation_angle: Deg<f32>,
+    orbit: Orbit,
+    star: Star,
+    atmosphere: Atmosphere,
+    subsurface: Subsurface,
+    clock: SimulationClock,
+    energy_balance: EnergyBalance,
 
     sea_level: f32,
+    regions: Vec<Region>,
+    resource_model: ResourceModel,
+    region_deposits: Vec<Deposits>,
+    biome_classifier: BiomeClassifier,
+    materials: MaterialRegistry,
+    moisture_model: MoistureModel,
+    circulation: Circulation,
+    ocean_currents: OceanCurrents,
+    heat_diffusion: HeatDiffusion,
+    ice_model: IceModel,
+    climate_means: ClimateMeans,
+    vegetation_model: VegetationModel,
+    ecosystem: Ecosystem,
+    settlement_model: SettlementModel,
+    settlements: Settlements,
 
     last_frame: f32,
     planet_type: PlanetType,
@@ -116,38 +175,139 @@ impl<'a> Planet<'a> {
         ];
 
     pub fn create(grid: Grid,
-                  sun_pos: Vector3<f32>,
                   surface_shader: &'a ShaderProgram<'a, PlanetVertex>,
                   atmosphere_shader: &'a ShaderProgram<'a, PlanetVertex>) -> Self {
 
         let surface_mesh  = Self::create_mesh(&grid);
 
-        Planet {
+        let mut planet = Planet {
             grid: grid,
             mesh: surface_mesh,
             surface_target: ShaderTarget::create(surface_shader),
             atmosphere_target: ShaderTarget::create(atmosphere_shader),
 
-            sun_pos: sun_pos,
-            rotation: Deg(0.0),
+            pos: Vector3::zero(),
+            radius: 6371000.0,
+            plates: Plates::default(),
+            rotation: Rotation::default(),
+            rotation_angle: Deg(0.0),
+            orbit: Orbit::default(),
+            star: Star::default(),
+            atmosphere: Atmosphere::default(),
+            subsurface: Subsurface::default(),
+            clock: SimulationClock::default(),
+            energy_balance: EnergyBalance::default(),
 
             sea_level: 0.0,
+            regions: Vec::new(),
+            resource_model: ResourceModel::default(),
+            region_deposits: Vec::new(),
+            biome_classifier: BiomeClassifier::default(),
+            materials: MaterialRegistry::default(),
+            moisture_model: MoistureModel::default(),
+            circulation: Circulation::default(),
+            ocean_currents: OceanCurrents::default(),
+            heat_diffusion: HeatDiffusion::default(),
+            ice_model: IceModel::default(),
+            climate_means: ClimateMeans::default(),
+            vegetation_model: VegetationModel::default(),
+            ecosystem: Ecosystem::default(),
+            settlement_model: SettlementModel::default(),
+            settlements: Settlements::default(),
 
             last_frame: 0.0,
             overlay: PlanetOverlay::Basic,
             planet_type: PlanetType::Empty,
+        };
+
+        planet.sync_rotation();
+        planet.set_height_range(0.0, 1000.0);
+        planet.classify_surface();
+        planet
+    }
+
+    /* Fills the heights and sets up the climate of a new planet in one go */
+    pub fn generate(grid: Grid,
+                    params: &PlanetParams,
+                    surface_shader: &'a ShaderProgram<'a, PlanetVertex>,
+                    atmosphere_shader: &'a ShaderProgram<'a, PlanetVertex>) -> Self {
+
+        let mut grid = grid;
+        Landscape::fill_heights(&mut grid, &params.terrain, params.min_height, params.max_height, params.sea_level);
+
+        if let Some(ref craters) = params.craters {
+            craters.stamp(&mut grid, params.radius);
+            Landscape::normalize_heights(&mut grid, params.min_height, params.max_height);
+        }
+
+        params.plates.assign(&mut grid);
+        if let Some(ref volcanism) = params.volcanism {
+            volcanism.apply(&mut grid, &params.plates, params.radius);
+        }
+
+        /* Volcanoes become the highest peaks of the curve, or the range is stretched back around them */
+        if let Some(ref hypsometry) = params.hypsometry {
+            Landscape::apply_hypsometry(&mut grid, hypsometry, params.min_height, params.max_height);
+        } else if params.volcanism.is_some() {
+            Landscape::normalize_heights(&mut grid, params.min_height, params.max_height);
+        }
+
+        let sea_level = match params.ocean_fraction {
+            Some(fraction) => Landscape::sea_level_for_ocean_fraction(&grid, fraction),
+            None => params.sea_level,
+        };
+
+        for t in &mut grid.tiles {
+            t.temperature = params.initial_temperature;
+            t.mean_air_temperature = params.initial_temperature;
         }
+
+        let mut planet = Planet::create(grid, surface_shader, atmosphere_shader);
+        planet.planet_type = params.planet_type;
+        planet.radius = params.radius;
+        planet.plates = params.plates.clone();
+        planet.orbit = params.orbit;
+        planet.star = params.star;
+        planet.rotation = params.rotation;
+        planet.atmosphere = params.atmosphere;
+        planet.materials = params.materials.clone();
+        planet.resource_model = params.resources;
+
+        planet.set_height_range(params.min_height as f32, params.max_height as f32);
+        planet.sync_rotation();
+        planet.set_sea_level(sea_level as f32);
+
+        planet
+    }
+
+    /* Share of the surface above the sea */
+    pub fn land_fraction(&self) -> f64 {
+        Landscape::land_fraction(&self.grid)
+    }
+
+    pub fn planet_type(&self) -> PlanetType {
+        self.planet_type
+    }
+
+    /* m */
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    pub fn plates(&self) -> &Plates {
+        &self.plates
     }
 
     pub fn create_mesh(grid: &Grid) -> Mesh<PlanetVertex> {
         GridMesh::create(grid, Planet::RADIUS, |pos, normal, tile: &PlanetTile| -> PlanetVertex {
             let mut vertex = PlanetVertex::new(pos, normal);            
-            Self::fill_vertex(&mut vertex, tile);
+            Self::fill_vertex(&mut vertex, tile, Vector3::zero());
             vertex
         })
     }
 
-    fn fill_vertex(vertex: &mut PlanetVertex, tile: &PlanetTile) {
+    fn fill_vertex(vertex: &mut PlanetVertex, tile: &PlanetTile, color: Vector3<f32>) {
+        vertex.color = color;
         vertex.brightness = tile.brightness;
         vertex.temperature = tile.temperature as f32;
         vertex.height = tile.height as f32;
@@ -155,6 +315,7 @@ impl<'a> Planet<'a> {
     }
 
     fn update_vertices(&mut self) {
+        let colors: Vec<Vector3<f32>> = self.grid.tiles.iter().map(|t| self.overlay_color(t)).collect();
         {
             let vertices = self.mesh.get_mut_vertices();
 
@@ -163,7 +324,7 @@ impl<'a> Planet<'a> {
 
                 for j in 0..tile.grid_tile.edge_count as usize {
                     let corner_id = Corner::get_id(tile.grid_tile.corners[j]);
-                    Self::fill_vertex(&mut vertices[corner_id], tile);
+                    Self::fill_vertex(&mut vertices[corner_id], tile, colors[i]);
                 }
             }
         }
@@ -172,9 +333,48 @@ impl<'a> Planet<'a> {
     }
     
 
+    /* m, the relief is drawn and the Basic overlay shaded between these */
+    pub fn set_height_range(&mut self, min_height: f32, max_height: f32) {
+        self.surface_target.set_uniform_f32("min_height", min_height);
+        self.surface_target.set_uniform_f32("max_height", max_height);
+    }
+
+    /* Floods or drains tiles, then relabels regions, redistributes the resources, gives up flooded settlements and
+       reclassifies biomes and materials so every simulation step after this one sees the new coastline. The overlays
+       are refilled right away, so the change shows while paused */
     pub fn set_sea_level(&mut self, sea_level: f32) {
         self.sea_level = sea_level;
         self.surface_target.set_uniform_f32("sea_level", sea_level);
+
+        Landscape::flood(&mut self.grid, sea_level as f64);
+        self.regions = Region::label(&mut self.grid);
+        self.region_deposits = self.resource_model.generate(&mut self.grid, &self.plates, &self.regions, self.radius, sea_level as f64);
+        self.settlements.flood(&mut self.grid);
+        self.classify_surface();
+        self.update_vertices();
+    }
+
+    pub fn sea_level(&self) -> f32 {
+        self.sea_level
+    }
+
+    /* Largest first, see Region::label */
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+
+    pub fn set_resource_model(&mut self, resource_model: ResourceModel) {
+        self.resource_model = resource_model;
+        self.region_deposits = self.resource_model.generate(&mut self.grid, &self.plates, &self.regions, self.radius, self.sea_level as f64);
+    }
+
+    pub fn deposits(&self, tile: usize) -> Deposits {
+        self.grid.tiles[tile].deposits
+    }
+
+    /* Totals over a region, see ResourceModel::generate */
+    pub fn region_deposits(&self, region: usize) -> Deposits {
+        self.region_deposits[region]
     }
 
     pub fn set_light(&mut self, direction: Vector3<f32>, color: Vector3<f32>) {
@@ -182,9 +382,338 @@ impl<'a> Planet<'a> {
         self.surface_target.set_uniform_vec3("light_color", color);
     }
 
+    /* Colour overlays are refilled right away, so switching works while paused */
     pub fn set_overlay(&mut self, overlay: PlanetOverlay) {
         self.overlay = overlay;
-        self.surface_target.set_uniform_i32("overlay", overlay as i32)
+        self.surface_target.set_uniform_i32("overlay", overlay as i32);
+        self.update_vertices();
+    }
+
+    pub fn set_orbit(&mut self, orbit: Orbit) {
+        self.orbit = orbit;
+        self.sync_rotation();
+    }
+
+    pub fn orbit(&self) -> &Orbit {
+        &self.orbit
+    }
+
+    pub fn set_star(&mut self, star: Star) {
+        self.star = star;
+    }
+
+    pub fn set_atmosphere(&mut self, atmosphere: Atmosphere) {
+        self.atmosphere = atmosphere;
+        self.classify_surface();
+    }
+
+    pub fn set_subsurface(&mut self, subsurface: Subsurface) {
+        self.subsurface = subsurface;
+    }
+
+    /* Global energy balance of the last simulation step */
+    pub fn energy_balance(&self) -> EnergyBalance {
+        self.energy_balance
+    }
+
+    pub fn set_clock(&mut self, clock: SimulationClock) {
+        self.clock = clock;
+    }
+
+    pub fn clock(&self) -> &SimulationClock {
+        &self.clock
+    }
+
+    /* Seconds of simulated time since the northern spring equinox of year 0 */
+    pub fn time(&self) -> f64 {
+        self.clock.time()
+    }
+
+    pub fn date(&self) -> SimulationDate {
+        self.clock.date(self.day_length(), self.orbit.year_length)
+    }
+
+    pub fn day_of_year(&self) -> u32 {
+        self.orbit.day_of_year(self.time(), self.day_length())
+    }
+
+    pub fn season(&self, northern: bool) -> Season {
+        self.orbit.season(self.time(), northern)
+    }
+
+    /* Degrees, positive in the northern summer */
+    pub fn subsolar_latitude(&self) -> f64 {
+        self.orbit.subsolar_latitude(self.time()).to_degrees()
+    }
+
+    /* One fixed step of the simulation at the current clock time */
+    fn simulate(&mut self, delta_t: f64) {
+        let time = self.time();
+        self.rotation_angle = Deg(self.rotation.angle(time, &self.orbit).to_degrees() as f32);
+
+        let sun_direction = self.orbit.sun_direction(time);
+        self.update_model_matrix();
+
+        let solar_flux = self.star.flux(self.orbit.distance(time));
+        self.energy_balance = Landscape::heat(&mut self.grid, &self.materials, &self.atmosphere, self.surface_target.get_model_matrix(), sun_direction, solar_flux, delta_t);
+        self.subsurface.update(&mut self.grid, &self.materials, delta_t);
+        let capacities = self.subsurface.surface_heat_capacities(&self.grid, &self.materials);
+        self.heat_diffusion.update(&mut self.grid, &capacities, self.radius, delta_t);
+        self.circulation.update(&mut self.grid, self.radius, delta_t);
+        self.ocean_currents.update(&mut self.grid, &self.circulation, self.radius, delta_t);
+
+        /* Temperatures are final for the step, everything below sees the air above them */
+        self.atmosphere.apply_elevation(&mut self.grid, self.sea_level as f64);
+        self.moisture_model.update(&mut self.grid, self.sea_level as f64, self.radius, delta_t);
+        self.vegetation_model.update(&mut self.grid, &self.moisture_model, delta_t);
+        self.ice_model.update(&mut self.grid, delta_t);
+
+        let averaging_time = f64::max(self.orbit.year_length, self.day_length());
+        self.climate_means.update(&mut self.grid, averaging_time, delta_t);
+        self.ecosystem.update(&self.grid, delta_t);
+        self.classify_surface();
+    }
+
+    fn update_model_matrix(&mut self) {
+        let tilt = Deg(-self.orbit.axial_tilt as f32);
+
+        for target in [&mut self.surface_target, &mut self.atmosphere_target].iter_mut() {
+            target.set_pos(self.pos);
+            target.set_rotation(Axis::X, tilt);
+            target.set_rotation(Axis::Y, self.rotation_angle);
+        }
+    }
+
+    pub fn set_biome_classifier(&mut self, biome_classifier: BiomeClassifier) {
+        self.biome_classifier = biome_classifier;
+        self.classify_surface();
+    }
+
+    pub fn set_materials(&mut self, materials: MaterialRegistry) {
+        self.materials = materials;
+        self.classify_surface();
+    }
+
+    pub fn materials(&self) -> &MaterialRegistry {
+        &self.materials
+    }
+
+    fn classify_surface(&mut self) {
+        self.atmosphere.apply_elevation(&mut self.grid, self.sea_level as f64);
+        self.biome_classifier.classify_grid(&mut self.grid, self.sea_level as f64);
+        self.materials.assign(&mut self.grid, self.sea_level as f64);
+        self.vegetation_model.apply_albedo(&mut self.grid);
+        self.ice_model.apply_albedo(&mut self.grid);
+    }
+
+    pub fn set_moisture_model(&mut self, moisture_model: MoistureModel) {
+        self.moisture_model = moisture_model;
+    }
+
+    pub fn set_circulation(&mut self, circulation: Circulation) {
+        self.circulation = circulation;
+        self.sync_rotation();
+    }
+
+    pub fn set_rotation(&mut self, rotation: Rotation) {
+        self.rotation = rotation;
+        self.sync_rotation();
+    }
+
+    pub fn rotation(&self) -> &Rotation {
+        &self.rotation
+    }
+
+    /* Seconds, see Rotation::day_length */
+    pub fn day_length(&self) -> f64 {
+        self.rotation.day_length(&self.orbit)
+    }
+
+    /* Coriolis forces follow the spin of the planet, a locked planet turns once a year */
+    fn sync_rotation(&mut self) {
+        self.circulation.rotation_rate = self.rotation.angular_velocity(&self.orbit);
+    }
+
+    pub fn set_ocean_currents(&mut self, ocean_currents: OceanCurrents) {
+        self.ocean_currents = ocean_currents;
+    }
+
+    pub fn set_heat_diffusion(&mut self, heat_diffusion: HeatDiffusion) {
+        self.heat_diffusion = heat_diffusion;
+    }
+
+    pub fn set_vegetation_model(&mut self, vegetation_model: VegetationModel) {
+        self.vegetation_model = vegetation_model;
+        self.classify_surface();
+    }
+
+    /* Populations start over from the best tile of each species */
+    pub fn set_ecosystem(&mut self, ecosystem: Ecosystem) {
+        self.ecosystem = ecosystem;
+    }
+
+    pub fn ecosystem(&self) -> &Ecosystem {
+        &self.ecosystem
+    }
+
+    pub fn set_settlement_model(&mut self, settlement_model: SettlementModel) {
+        self.settlement_model = settlement_model;
+    }
+
+    /* Settles the planet on its mean climate, best after the simulation has run for a year */
+    pub fn settle(&mut self) {
+        self.settlements = self.settlement_model.generate(&mut self.grid, self.radius, self.sea_level as f64);
+        self.update_vertices();
+    }
+
+    pub fn settlements(&self) -> &Settlements {
+        &self.settlements
+    }
+
+    pub fn set_ice_model(&mut self, ice_model: IceModel) {
+        self.ice_model = ice_model;
+        self.classify_surface();
+    }
+
+    /* Colour overlays are computed here, scalar overlays (heights, brightness, temperature) in the shader */
+    fn overlay_color(&self, t: &PlanetTile) -> Vector3<f32> {
+        match self.overlay {
+            PlanetOverlay::Biomes => t.biome.color(),
+            PlanetOverlay::Currents => Self::current_color(t),
+            PlanetOverlay::Ice => Self::ice_color(t),
+            PlanetOverlay::Pressure => Self::pressure_color(t, self.atmosphere.surface_pressure),
+            PlanetOverlay::Vegetation => Self::vegetation_color(t, self.vegetation_model.max_biomass),
+            PlanetOverlay::Species => self.species_color(t),
+            PlanetOverlay::Settlements => Self::settlement_color(t),
+            PlanetOverlay::Resources => Self::resource_color(t, self.radius),
+            _ => Vector3::zero(),
+        }
+    }
+
+    /* Hue shows the direction of the current, brightness its speed */
+    fn current_color(t: &PlanetTile) -> Vector3<f32> {
+        const FULL_SPEED: f32 = 0.3; // m/s
+
+        if !t.has_water {
+            return Vector3::new(0.2, 0.2, 0.2);
+        }
+
+        let (east, north) = Transport::local_basis(t.grid_tile.pos);
+        let hue = t.current.dot(north).atan2(t.current.dot(east)) / (2.0 * PI) + 0.5;
+        let value = f32::min(1.0, t.current.magnitude() / FULL_SPEED);
+
+        Self::hsv_color(hue, 1.0, value)
+    }
+
+    /* White for thick ice sheets, fading to the bare surface below a centimetre */
+    fn ice_color(t: &PlanetTile) -> Vector3<f32> {
+        const SHEET_THICKNESS: f32 = 1000.0; // m
+
+        let bare = if t.has_water { Vector3::new(0.05, 0.1, 0.3) } else { Vector3::new(0.3, 0.25, 0.2) };
+
+        if t.ice_thickness < 0.01 {
+            return bare;
+        }
+
+        let amount = f32::min(1.0, (1.0 + t.ice_thickness as f32).ln() / (1.0 + SHEET_THICKNESS).ln());
+        Vector3::new(0.6, 0.8, 0.9) * (1.0 - amount) + Vector3::new(1.0, 1.0, 1.0) * amount
+    }
+
+    /* Bare ground brown, greening with cover and darkening as forests build up biomass */
+    fn vegetation_color(t: &PlanetTile, max_biomass: f64) -> Vector3<f32> {
+        if t.has_water {
+            return Vector3::new(0.1, 0.15, 0.3);
+        }
+
+        let cover = t.vegetation as f32;
+        let maturity = f32::min(1.0, (t.biomass / max_biomass) as f32).sqrt();
+        let green = Vector3::new(0.5, 0.8, 0.2) * (1.0 - maturity) + Vector3::new(0.05, 0.35, 0.05) * maturity;
+
+        Vector3::new(0.55, 0.45, 0.3) * (1.0 - cover) + green * cover
+    }
+
+    /* Colour of the dominant species, fading to grey as the tile empties */
+    fn species_color(&self, t: &PlanetTile) -> Vector3<f32> {
+        let id = t.grid_tile.id;
+        let empty = if t.has_water { Vector3::new(0.1, 0.1, 0.2) } else { Vector3::new(0.25, 0.25, 0.25) };
+
+        match self.ecosystem.dominant(id) {
+            Some(s) => {
+                let [r, g, b] = self.ecosystem.species[s].color;
+                let amount = f32::min(1.0, self.ecosystem.population(s, id) as f32).sqrt();
+                empty * (1.0 - amount) + Vector3::new(r, g, b) * amount
+            },
+            None => empty,
+        }
+    }
+
+    /* Routes red, settled tiles yellow brightening up to a million people, the rest green by habitability */
+    fn settlement_color(t: &PlanetTile) -> Vector3<f32> {
+        if t.has_water {
+            return Vector3::new(0.05, 0.1, 0.25);
+        }
+        if t.route {
+            return Vector3::new(0.9, 0.15, 0.1);
+        }
+
+        let habitable = Vector3::new(0.2, 0.2, 0.2) * (1.0 - t.habitability as f32) + Vector3::new(0.2, 0.5, 0.2) * t.habitability as f32;
+
+        match t.settlement {
+            Some(_) => {
+                let amount = 0.5 + 0.5 * f32::min(1.0, (1.0 + t.population as f32).log10() / 6.0);
+                habitable * (1.0 - amount) + Vector3::new(1.0, 0.9, 0.3) * amount
+            },
+            None => habitable,
+        }
+    }
+
+    /* Rivers and lakes blue, oil purple, ore orange, the rest of the land greener with more fertile soil */
+    fn resource_color(t: &PlanetTile, planet_radius: f64) -> Vector3<f32> {
+        let deposits = &t.deposits;
+
+        if t.has_water {
+            return if deposits.oil > 0.0 { Vector3::new(0.4, 0.1, 0.4) }
+                else if deposits.fresh_water > 0.0 { Vector3::new(0.3, 0.6, 1.0) }
+                else { Vector3::new(0.05, 0.1, 0.25) };
+        }
+
+        if deposits.fresh_water > 0.0 {
+            return Vector3::new(0.2, 0.4, 1.0);
+        }
+        if deposits.oil > 0.0 {
+            return Vector3::new(0.5, 0.1, 0.5);
+        }
+        if deposits.ore > 0.0 {
+            return Vector3::new(1.0, 0.5, 0.1);
+        }
+
+        let area = t.grid_tile.area() as f64 * planet_radius * planet_radius / 1.0e6; // km^2
+        let fertile = f32::min(1.0, (deposits.fertile_soil / area) as f32);
+        Vector3::new(0.45, 0.4, 0.3) * (1.0 - fertile) + Vector3::new(0.2, 0.6, 0.1) * fertile
+    }
+
+    /* Relative to the sea level pressure, dark for thin air */
+    fn pressure_color(t: &PlanetTile, surface_pressure: f64) -> Vector3<f32> {
+        let ratio = if surface_pressure > 0.0 { (t.pressure / surface_pressure) as f32 } else { 0.0 };
+        Vector3::new(ratio, ratio * 0.8, 0.2 + 0.8 * ratio)
+    }
+
+    fn hsv_color(hue: f32, saturation: f32, value: f32) -> Vector3<f32> {
+        let h = (hue - hue.floor()) * 6.0;
+        let f = h - h.floor();
+
+        let p = value * (1.0 - saturation);
+        let q = value * (1.0 - saturation * f);
+        let t = value * (1.0 - saturation * (1.0 - f));
+
+        match h as i32 {
+            0 => Vector3::new(value, t, p),
+            1 => Vector3::new(q, value, p),
+            2 => Vector3::new(p, value, t),
+            3 => Vector3::new(p, q, value),
+            4 => Vector3::new(t, p, value),
+            _ => Vector3::new(value, p, q),
+        }
     }
 
     fn get_color(t: &PlanetTile) -> Vector3<f32> {
@@ -196,24 +725,26 @@ impl<'a> Planet<'a> {
 
 impl<'a> RenderTarget for Planet<'a> {
     fn update(&mut self, camera: &Camera, time: f32) {
+        let steps = self.clock.advance(time as f64);
 
-        let degrees_per_tick = 1.0;
-        let seconds_per_tick = (24.0 * 60.0 * 60.0) / (360.0 / degrees_per_tick);
+        for _ in 0..steps {
+            self.clock.tick();
+            let delta_t = self.clock.time_step;
+            self.simulate(delta_t);
+        }
 
-        self.rotation += Deg(degrees_per_tick);
+        let sun_direction = self.orbit.sun_direction(self.time());
 
-        self.surface_target.set_rotation(Axis::Y, Deg(degrees_per_tick));
-        self.atmosphere_target.set_rotation(Axis::Y, Deg(degrees_per_tick));
+        self.update_model_matrix();
+        self.set_light(sun_direction, Vector3::new(1.0, 1.0, 1.0));
 
         self.surface_target.update(camera);
         self.atmosphere_target.update(camera);
 
-        Landscape::heat(&mut self.grid, self.surface_target.get_model_matrix(), self.sun_pos, seconds_per_tick as f64);
-
-        //if time - self.last_frame > 0.5 {
+        if steps > 0 {
             self.update_vertices();
             self.last_frame = time;
-        //}
+        }
     }
 
     fn process_key_pressed(&mut self, key: Key) {
@@ -221,6 +752,21 @@ impl<'a> RenderTarget for Planet<'a> {
             Key::Num1 => { self.set_overlay(PlanetOverlay::Basic); },
             Key::Num2 => { self.set_overlay(PlanetOverlay::Brightness); },
             Key::Num3 => { self.set_overlay(PlanetOverlay::Temperature); },
+            Key::Num4 => { self.set_overlay(PlanetOverlay::Biomes); },
+            Key::Num5 => { self.set_overlay(PlanetOverlay::Currents); },
+            Key::Num6 => { self.set_overlay(PlanetOverlay::Ice); },
+            Key::Num7 => { self.set_overlay(PlanetOverlay::Pressure); },
+            Key::Num8 => { self.set_overlay(PlanetOverlay::Vegetation); },
+            Key::Num9 => { self.set_overlay(PlanetOverlay::Species); },
+            Key::Num0 => { self.set_overlay(PlanetOverlay::Settlements); },
+            Key::T => { self.settle(); },
+            Key::M => { self.set_overlay(PlanetOverlay::Resources); },
+            Key::P => { self.clock.toggle_pause(); },
+            Key::N => { self.clock.single_step(); },
+            Key::Equal => { self.clock.faster(); },
+            Key::Minus => { self.clock.slower(); },
+            Key::PageUp => { let sea_level = self.sea_level + 10.0; self.set_sea_level(sea_level); },
+            Key::PageDown => { let sea_level = self.sea_level - 10.0; self.set_sea_level(sea_level); },
             _ => {},
         }
     }
@@ -237,7 +783,7 @@ impl<'a> RenderTarget for Planet<'a> {
     }
 
     fn set_pos(&mut self, pos: Vector3<f32>) {
-        self.surface_target.set_pos(pos);
-        self.atmosphere_target.set_pos(pos);
+        self.pos = pos;
+        self.update_model_matrix();
     }
 }
\ No newline at end of file
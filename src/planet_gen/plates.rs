@@ -0,0 +1,88 @@
+extern crate cgmath;
+extern crate rand;
+
+use self::cgmath::{ Vector3, Matrix3, InnerSpace, Rad };
+use self::rand::{ Rng, SeedableRng };
+use self::rand::rngs::StdRng;
+
+use sphere_grid::grid::Grid;
+use sphere_grid::sphere::Sphere;
+
+/* Rigid piece of the lithosphere turning about its Euler pole */
+#[derive(Copy, Clone, Debug)]
+pub struct Plate {
+    pub centre: Vector3<f32>,
+    pub pole: Vector3<f32>,
+    pub rate: f64, // rad per year
+}
+
+/* Voronoi plates around their centres, each tile belongs to the nearest one (PlanetTile::plate).
+   A single plate is a uniform drift of the whole surface */
+#[derive(Clone, Debug)]
+pub struct Plates {
+    pub plates: Vec<Plate>,
+}
+
+impl Default for Plates {
+    fn default() -> Self {
+        Plates::stationary()
+    }
+}
+
+#[allow(dead_code)]
+impl Plates {
+    /* Up to max_speed (m/yr) on a planet of planet_radius (m), Earth's plates move a few cm a year */
+    pub fn random(count: usize, seed: u64, max_speed: f64, planet_radius: f64) -> Plates {
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        Plates {
+            plates: (0..usize::max(1, count)).map(|_| Plate {
+                centre: Sphere::random_direction(&mut rng),
+                pole: Sphere::random_direction(&mut rng),
+                rate: rng.gen_range(0.2, 1.0) * max_speed / planet_radius,
+            }).collect(),
+        }
+    }
+
+    pub fn drift(pole: Vector3<f32>, rate: f64) -> Plates {
+        Plates {
+            plates: vec![Plate { centre: pole, pole: pole.normalize(), rate: rate }],
+        }
+    }
+
+    pub fn stationary() -> Plates {
+        Plates::drift(Vector3::new(0.0, 1.0, 0.0), 0.0)
+    }
+
+    pub fn plate_at(&self, pos: Vector3<f32>) -> usize {
+        let mut best = 0;
+
+        for (i, plate) in self.plates.iter().enumerate() {
+            if plate.centre.dot(pos) > self.plates[best].centre.dot(pos) {
+                best = i;
+            }
+        }
+
+        best
+    }
+
+    pub fn assign(&self, grid: &mut Grid) {
+        for t in &mut grid.tiles {
+            t.plate = self.plate_at(t.grid_tile.pos);
+        }
+    }
+
+    /* Where a point of the given plate is carried over years */
+    pub fn carry(&self, plate: usize, pos: Vector3<f32>, years: f64) -> Vector3<f32> {
+        let plate = &self.plates[plate];
+        let angle = (plate.rate * years) as f32;
+
+        (Matrix3::from_axis_angle(plate.pole, Rad(angle)) * pos).normalize()
+    }
+
+    /* Surface velocity in radians per year, tangent to the unit sphere */
+    pub fn velocity(&self, plate: usize, pos: Vector3<f32>) -> Vector3<f32> {
+        let plate = &self.plates[plate];
+        plate.pole.cross(pos) * plate.rate as f32
+    }
+}
@@ -0,0 +1,187 @@
+use planet_gen::atmosphere::Atmosphere;
+use planet_gen::craters::CraterField;
+use planet_gen::landscape::Hypsometry;
+use planet_gen::material::MaterialRegistry;
+use planet_gen::noise_graph::NoiseGraph;
+use planet_gen::orbit::Orbit;
+use planet_gen::plates::Plates;
+use planet_gen::planet::PlanetType;
+use planet_gen::resources::ResourceModel;
+use planet_gen::rotation::Rotation;
+use planet_gen::star::Star;
+use planet_gen::volcanism::Volcanism;
+
+/* Everything Planet::generate needs to build a planet. Start from a preset and override single fields:
+   PlanetParams { sea_level: 300.0, ..PlanetParams::preset(PlanetType::Desert) } */
+#[derive(Clone, Debug)]
+pub struct PlanetParams {
+    pub planet_type: PlanetType,
+
+    pub radius: f64, // m
+    pub terrain: NoiseGraph,
+    pub craters: Option<CraterField>,
+    pub plates: Plates,
+    pub volcanism: Option<Volcanism>,
+    pub min_height: f64, // m, of the deepest point
+    pub max_height: f64, // m, of the highest peak
+    pub hypsometry: Option<Hypsometry>, // heights are remapped to this curve when set
+    pub ocean_fraction: Option<f64>, // sea level is set to flood this share of the surface when set
+    pub sea_level: f64, // m, used without an ocean fraction
+    pub initial_temperature: f64, // °C, close to the expected climate so it settles quickly
+    pub resources: ResourceModel,
+
+    pub orbit: Orbit,
+    pub star: Star,
+    pub rotation: Rotation,
+    pub atmosphere: Atmosphere,
+    pub materials: MaterialRegistry,
+}
+
+impl Default for PlanetParams {
+    fn default() -> Self {
+        PlanetParams::preset(PlanetType::EarthLike)
+    }
+}
+
+impl PlanetParams {
+    pub fn preset(planet_type: PlanetType) -> PlanetParams {
+        let terrain = NoiseGraph::default();
+        let earth_radius = 6371000.0;
+
+        let earth = PlanetParams {
+            planet_type: planet_type,
+
+            radius: earth_radius,
+            terrain: terrain.clone(),
+            craters: None,
+            plates: Plates::random(12, 0, 0.08, earth_radius),
+            volcanism: Some(Volcanism::random(5, 0, 4000.0)),
+            min_height: -8000.0,
+            max_height: 8000.0,
+            hypsometry: Some(Hypsometry::earth()),
+            ocean_fraction: Some(0.71),
+            sea_level: 0.0,
+            initial_temperature: 15.0,
+            resources: ResourceModel::default(),
+
+            orbit: Orbit::default(),
+            star: Star::default(),
+            rotation: Rotation::default(),
+            atmosphere: Atmosphere::earth(),
+            materials: MaterialRegistry::default(),
+        };
+
+        match planet_type {
+            PlanetType::Empty | PlanetType::EarthLike => earth,
+
+            PlanetType::Desert => PlanetParams {
+                terrain: terrain.reseed(17),
+                hypsometry: None,
+                ocean_fraction: Some(0.1),
+                initial_temperature: 25.0,
+                resources: ResourceModel { rainfall: 5.0e-6, ..ResourceModel::default() },
+                orbit: Orbit { semi_major_axis: 0.9 * Orbit::ASTRONOMICAL_UNIT, ..Orbit::default() },
+                ..earth
+            },
+
+            PlanetType::Ocean => PlanetParams {
+                terrain: terrain.reseed(5),
+                ocean_fraction: Some(0.95),
+                ..earth
+            },
+
+            PlanetType::Ice => PlanetParams {
+                terrain: terrain.reseed(41),
+                ocean_fraction: Some(0.6),
+                initial_temperature: -35.0,
+                orbit: Orbit { semi_major_axis: 1.5 * Orbit::ASTRONOMICAL_UNIT, ..Orbit::default() },
+                ..earth
+            },
+
+            /* Close to its star with no air and no seas, about 1500 °C at the substellar point */
+            PlanetType::Lava => PlanetParams {
+                terrain: terrain.reseed(3),
+                hypsometry: None,
+                ocean_fraction: None,
+                sea_level: -8000.0, // the deepest point, nothing is flooded
+                initial_temperature: 1000.0,
+                resources: ResourceModel::dry(),
+                orbit: Orbit {
+                    semi_major_axis: 0.05 * Orbit::ASTRONOMICAL_UNIT,
+                    eccentricity: 0.0,
+                    year_length: 4.0 * 24.0 * 60.0 * 60.0,
+                    axial_tilt: 0.0,
+                    ..Orbit::default()
+                },
+                rotation: Rotation { period: 10.0 * 60.0 * 60.0, ..Rotation::default() },
+                atmosphere: Atmosphere::none(),
+                materials: MaterialRegistry::parse(include_str!("../../assets/materials_lava.toml")),
+                ..earth
+            },
+
+            /* Eyeball world around a red dwarf, Earth's insolation at the substellar point */
+            PlanetType::TidallyLocked => PlanetParams {
+                terrain: terrain.reseed(23),
+                initial_temperature: -20.0,
+                orbit: Orbit {
+                    semi_major_axis: 0.05 * Orbit::ASTRONOMICAL_UNIT,
+                    eccentricity: 0.01,
+                    year_length: 9.0 * 24.0 * 60.0 * 60.0,
+                    axial_tilt: 0.0,
+                    ..Orbit::default()
+                },
+                star: Star { luminosity: 0.0025 * Star::SOLAR_LUMINOSITY },
+                rotation: Rotation::tidally_locked(),
+                ..earth
+            },
+
+            /* Airless and cratered, the Sun rises once a month */
+            PlanetType::Moon => PlanetParams {
+                radius: 1737400.0,
+                terrain: terrain.reseed(57),
+                craters: Some(CraterField::moon()),
+                plates: Plates::stationary(),
+                volcanism: None,
+                min_height: -9000.0,
+                max_height: 10000.0,
+                hypsometry: None,
+                ocean_fraction: None,
+                sea_level: -9000.0,
+                initial_temperature: -20.0,
+                resources: ResourceModel::dry(),
+                orbit: Orbit { axial_tilt: 1.54, ..Orbit::default() },
+                rotation: Rotation { period: 27.32 * 24.0 * 60.0 * 60.0, ..Rotation::default() },
+                atmosphere: Atmosphere::none(),
+                materials: MaterialRegistry::parse(include_str!("../../assets/materials_regolith.toml")),
+                ..earth
+            },
+
+            /* Three turns in two of its years around the Sun */
+            PlanetType::Mercury => PlanetParams {
+                radius: 2439700.0,
+                terrain: terrain.reseed(61),
+                craters: Some(CraterField::mercury()),
+                plates: Plates::stationary(),
+                volcanism: None,
+                min_height: -5000.0,
+                max_height: 4500.0,
+                hypsometry: None,
+                ocean_fraction: None,
+                sea_level: -5000.0,
+                initial_temperature: 100.0,
+                resources: ResourceModel::dry(),
+                orbit: Orbit {
+                    semi_major_axis: 0.387 * Orbit::ASTRONOMICAL_UNIT,
+                    eccentricity: 0.2056,
+                    year_length: 87.97 * 24.0 * 60.0 * 60.0,
+                    axial_tilt: 0.03,
+                    perihelion_longitude: 77.5,
+                },
+                rotation: Rotation { period: 58.65 * 24.0 * 60.0 * 60.0, ..Rotation::default() },
+                atmosphere: Atmosphere::none(),
+                materials: MaterialRegistry::parse(include_str!("../../assets/materials_regolith.toml")),
+                ..earth
+            },
+        }
+    }
+}
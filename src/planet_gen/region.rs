@@ -0,0 +1,74 @@
+use std::collections::VecDeque;
+
+use sphere_grid::grid::Grid;
+use sphere_grid::tile::GridTile;
+
+/* Connected stretch of land (continent, island) or water (ocean, lake). Tiles point to theirs with PlanetTile::region */
+#[derive(Clone, Debug)]
+pub struct Region {
+    pub id: usize,
+    pub is_water: bool,
+    pub tile_count: usize,
+    pub area: f64, // share of the surface
+}
+
+#[allow(dead_code)]
+impl Region {
+    /* Labels every tile and returns the regions, largest first */
+    pub fn label(grid: &mut Grid) -> Vec<Region> {
+        const UNLABELLED: usize = usize::max_value();
+
+        let mut labels = vec![UNLABELLED; grid.tiles.len()];
+        let mut regions = Vec::new();
+        let total_area: f64 = grid.tiles.iter().map(|t| t.grid_tile.area() as f64).sum();
+
+        for start in 0..grid.tiles.len() {
+            if labels[start] != UNLABELLED {
+                continue;
+            }
+
+            let is_water = grid.tiles[start].has_water;
+            let mut region = Region { id: regions.len(), is_water: is_water, tile_count: 0, area: 0.0 };
+
+            let mut queue = VecDeque::new();
+            labels[start] = region.id;
+            queue.push_back(start);
+
+            while let Some(i) = queue.pop_front() {
+                let tile = &grid.tiles[i].grid_tile;
+                region.tile_count += 1;
+                region.area += tile.area() as f64 / total_area;
+
+                for k in 0..tile.edge_count as usize {
+                    let n = GridTile::get_id(tile.tiles[k]);
+
+                    if labels[n] == UNLABELLED && grid.tiles[n].has_water == is_water {
+                        labels[n] = region.id;
+                        queue.push_back(n);
+                    }
+                }
+            }
+
+            regions.push(region);
+        }
+
+        /* Renumber by size so the main ocean and the largest continent come first */
+        regions.sort_by(|a, b| b.area.partial_cmp(&a.area).unwrap());
+
+        let mut new_ids = vec![0; regions.len()];
+        for (id, region) in regions.iter_mut().enumerate() {
+            new_ids[region.id] = id;
+            region.id = id;
+        }
+
+        for (t, label) in grid.tiles.iter_mut().zip(labels) {
+            t.region = new_ids[label];
+        }
+
+        regions
+    }
+
+    pub fn is_land(&self) -> bool {
+        !self.is_water
+    }
+}
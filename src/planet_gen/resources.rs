@@ -0,0 +1,302 @@
+extern crate cgmath;
+extern crate rand;
+
+use std::f64;
+use std::collections::BinaryHeap;
+
+use self::cgmath::InnerSpace;
+use self::rand::{ Rng, SeedableRng };
+use self::rand::rngs::StdRng;
+
+use sphere_grid::grid::Grid;
+use sphere_grid::tile::GridTile;
+use sphere_grid::random::Random;
+use sphere_grid::visit::Visit;
+use planet_gen::plates::Plates;
+use planet_gen::region::Region;
+
+#[allow(dead_code)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Resource {
+    Ore,
+    Oil,
+    FertileSoil,
+    FreshWater,
+}
+
+/* Resources on a tile (PlanetTile::deposits) or in a whole region */
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct Deposits {
+    pub ore: f64, // t of metal
+    pub oil: f64, // m^3
+    pub fertile_soil: f64, // km^2 of arable land
+    pub fresh_water: f64, // m^3/s, river discharge on land, inflow on lakes
+}
+
+#[allow(dead_code)]
+impl Deposits {
+    pub fn get(&self, resource: Resource) -> f64 {
+        match resource {
+            Resource::Ore => self.ore,
+            Resource::Oil => self.oil,
+            Resource::FertileSoil => self.fertile_soil,
+            Resource::FreshWater => self.fresh_water,
+        }
+    }
+}
+
+/* Places resources following the geology rather than at random:
+   - ore near plate boundaries, most where the plates converge, and around volcanoes
+   - oil in sediment basins, lowlands and shelf seas below the surrounding terrain and off river mouths, away from
+     volcanoes
+   - fertile soil along rivers, on volcanic ash and in basins, where it rains
+   - fresh water in rivers and lakes, water regions smaller than max_lake_area. The uniform rainfall drains downhill
+     to the sea, through depressions as if they were filled, and tiles carrying more than river_discharge count as
+     rivers
+   Ore and oil come as single deposits of random size, the geology sets how likely a tile is to hold one. Nothing is
+   read from the simulated climate, so the deposits only depend on the terrain, the sea level and the seed */
+#[derive(Copy, Clone, Debug)]
+pub struct ResourceModel {
+    pub seed: u64,
+
+    pub convergence_speed: f64, // m/yr of plate convergence for the richest boundaries
+    pub divergent_ore: f64, // ore likelihood at spreading boundaries relative to converging ones
+    pub boundary_rings: usize, // tiles either side of a boundary that share its ore
+    pub volcanic_ore: f64, // ore likelihood on volcanoes
+    pub ore_chance: f64, // of a deposit where the geology is most favourable
+    pub ore_size: f64, // t, median deposit
+
+    pub basin_depth: f64, // m below the surroundings for a full basin
+    pub shelf_depth: f64, // m below sea level still collecting sediment
+    pub oil_chance: f64,
+    pub oil_size: f64, // m^3, median deposit
+
+    pub rainfall: f64, // kg/m^2/s, everywhere
+    pub runoff: f64, // share of the rain reaching the rivers
+    pub river_discharge: f64, // m^3/s
+    pub max_lake_area: f64, // share of the surface
+    pub soil_age: f64, // years for volcanic soils to lose their fertility
+    pub wet_half_fertility: f64, // kg/m^2/s of rain halving fertility
+}
+
+impl Default for ResourceModel {
+    fn default() -> Self {
+        ResourceModel {
+            seed: 0,
+
+            convergence_speed: 0.05,
+            divergent_ore: 0.4,
+            boundary_rings: 3,
+            volcanic_ore: 0.6,
+            ore_chance: 0.3,
+            ore_size: 1.0e6,
+
+            basin_depth: 300.0,
+            shelf_depth: 200.0,
+            oil_chance: 0.3,
+            oil_size: 1.0e8,
+
+            rainfall: 3.0e-5,
+            runoff: 0.4,
+            river_discharge: 500.0,
+            max_lake_area: 0.002,
+            soil_age: 10.0e6,
+            wet_half_fertility: 1.0e-5,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl ResourceModel {
+    /* No rain and no resources that need it */
+    pub fn dry() -> ResourceModel {
+        ResourceModel { rainfall: 0.0, ..ResourceModel::default() }
+    }
+
+    /* Sets PlanetTile::deposits and returns the totals of every region. Regional fresh water is what leaves the
+       region's rivers at their mouths, or flows into its lakes, so rivers are not counted once per tile */
+    pub fn generate(&self, grid: &mut Grid, plates: &Plates, regions: &[Region], planet_radius: f64, sea_level: f64) -> Vec<Deposits> {
+        let (downstream, discharge) = self.drainage(grid, planet_radius);
+        let boundaries = self.boundaries(grid, plates, planet_radius);
+        let sediment = self.sediment(grid, &downstream, &discharge, sea_level);
+
+        for (i, t) in grid.tiles.iter_mut().enumerate() {
+            let area = t.grid_tile.area() as f64 * planet_radius * planet_radius / 1.0e6; // km^2
+            let volcanic = if t.volcanic_age.is_finite() { 1.0 } else { 0.0 };
+            let is_lake = t.has_water && regions[t.region].area < self.max_lake_area;
+
+            let ore_likelihood = f64::min(1.0, boundaries[i] + self.volcanic_ore * volcanic) * self.ore_chance;
+            let oil_likelihood = sediment[i] * (1.0 - volcanic) * self.oil_chance;
+
+            /* Every tile draws the same numbers from its own stream, so deposits stay put when the geology around
+               them or the sea level changes */
+            let mut rng = StdRng::seed_from_u64(self.seed ^ (t.grid_tile.id as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            let (ore_roll, ore_size) = (rng.gen::<f64>(), Random::gaussian(&mut rng).exp());
+            let (oil_roll, oil_size) = (rng.gen::<f64>(), Random::gaussian(&mut rng).exp());
+
+            t.deposits = Deposits::default();
+
+            if ore_roll < ore_likelihood {
+                t.deposits.ore = self.ore_size * ore_size;
+            }
+            if oil_roll < oil_likelihood {
+                t.deposits.oil = self.oil_size * oil_size;
+            }
+
+            if !t.has_water {
+                if discharge[i] >= self.river_discharge {
+                    t.deposits.fresh_water = discharge[i];
+                }
+
+                let wetness = self.rainfall / (self.rainfall + self.wet_half_fertility);
+                let alluvial = discharge[i] / (discharge[i] + self.river_discharge);
+                let ash = if t.volcanic_age.is_finite() { (-t.volcanic_age / self.soil_age).exp() } else { 0.0 };
+
+                t.deposits.fertile_soil = area * wetness * f64::min(1.0, 0.3 + alluvial + ash + 0.5 * sediment[i]);
+            } else if is_lake {
+                t.deposits.fresh_water = discharge[i];
+            }
+        }
+
+        let mut totals = vec![Deposits::default(); regions.len()];
+
+        for (i, t) in grid.tiles.iter().enumerate() {
+            let total = &mut totals[t.region];
+            total.ore += t.deposits.ore;
+            total.oil += t.deposits.oil;
+            total.fertile_soil += t.deposits.fertile_soil;
+
+            let mouth = !t.has_water && downstream[i].map_or(true, |d| grid.tiles[d].has_water);
+            if mouth {
+                total.fresh_water += discharge[i];
+            } else if t.has_water {
+                total.fresh_water += t.deposits.fresh_water;
+            }
+        }
+
+        totals
+    }
+
+    /* Priority flood from the sea (or the lowest tile of a dry planet), so every land tile has a way down to the
+       water. Returns where each tile drains to and the discharge through it in m^3/s */
+    fn drainage(&self, grid: &Grid, planet_radius: f64) -> (Vec<Option<usize>>, Vec<f64>) {
+        let count = grid.tiles.len();
+        let mut downstream = vec![None; count];
+        let mut visited = vec![false; count];
+        let mut order = Vec::with_capacity(count);
+        let mut heap = BinaryHeap::new();
+
+        for (i, t) in grid.tiles.iter().enumerate() {
+            if t.has_water {
+                visited[i] = true;
+                heap.push(Visit { cost: t.height, tile: i });
+            }
+        }
+
+        if heap.is_empty() {
+            let lowest = (0..count).fold(0, |best, i| if grid.tiles[i].height < grid.tiles[best].height { i } else { best });
+            visited[lowest] = true;
+            heap.push(Visit { cost: grid.tiles[lowest].height, tile: lowest });
+        }
+
+        while let Some(Visit { cost: height, tile }) = heap.pop() {
+            order.push(tile);
+
+            let grid_tile = &grid.tiles[tile].grid_tile;
+            for k in 0..grid_tile.edge_count as usize {
+                let n = GridTile::get_id(grid_tile.tiles[k]);
+                if visited[n] {
+                    continue;
+                }
+
+                visited[n] = true;
+                downstream[n] = Some(tile);
+                heap.push(Visit { cost: f64::max(height, grid.tiles[n].height), tile: n });
+            }
+        }
+
+        /* Upstream tiles were reached last, so the reversed order passes the water on downhill */
+        let mut discharge: Vec<f64> = grid.tiles.iter().map(|t| {
+            let area = t.grid_tile.area() as f64 * planet_radius * planet_radius;
+            if t.has_water { 0.0 } else { self.rainfall * self.runoff * area / 1000.0 }
+        }).collect();
+
+        for i in order.into_iter().rev() {
+            if let Some(d) = downstream[i] {
+                discharge[d] += discharge[i];
+            }
+        }
+
+        (downstream, discharge)
+    }
+
+    /* 0..1 ore likelihood from plate boundaries, fading over boundary_rings tiles */
+    fn boundaries(&self, grid: &Grid, plates: &Plates, planet_radius: f64) -> Vec<f64> {
+        let mut strength: Vec<f64> = grid.tiles.iter().map(|t| {
+            let tile = &t.grid_tile;
+            let velocity = plates.velocity(t.plate, tile.pos);
+
+            (0..tile.edge_count as usize).map(|k| {
+                let n = &grid.tiles[GridTile::get_id(tile.tiles[k])];
+                if n.plate == t.plate {
+                    return 0.0;
+                }
+
+                let relative = plates.velocity(n.plate, n.grid_tile.pos) - velocity;
+                let closing = -relative.dot((n.grid_tile.pos - tile.pos).normalize()) as f64 * planet_radius;
+                let speed = f64::min(1.0, closing.abs() / self.convergence_speed);
+
+                if closing > 0.0 { speed } else { self.divergent_ore * speed }
+            }).fold(0.0, f64::max)
+        }).collect();
+
+        let fade = 0.5f64.powf(1.0 / self.boundary_rings.max(1) as f64);
+
+        for _ in 0..self.boundary_rings {
+            let previous = strength.clone();
+
+            for (i, t) in grid.tiles.iter().enumerate() {
+                let tile = &t.grid_tile;
+                for k in 0..tile.edge_count as usize {
+                    strength[i] = f64::max(strength[i], previous[GridTile::get_id(tile.tiles[k])] * fade);
+                }
+            }
+        }
+
+        strength
+    }
+
+    /* 0..1, how deep a tile lies below its smoothed surroundings, plus the sea off large river mouths */
+    fn sediment(&self, grid: &Grid, downstream: &[Option<usize>], discharge: &[f64], sea_level: f64) -> Vec<f64> {
+        let mut smooth: Vec<f64> = grid.tiles.iter().map(|t| t.height).collect();
+
+        for _ in 0..4 {
+            let previous = smooth.clone();
+
+            for (i, t) in grid.tiles.iter().enumerate() {
+                let tile = &t.grid_tile;
+                let sum: f64 = (0..tile.edge_count as usize).map(|k| previous[GridTile::get_id(tile.tiles[k])]).sum();
+                smooth[i] = (previous[i] + sum) / (1 + tile.edge_count as usize) as f64;
+            }
+        }
+
+        let mut sediment: Vec<f64> = grid.tiles.iter().zip(smooth.iter()).map(|(t, s)| {
+            if t.height < sea_level - self.shelf_depth {
+                0.0
+            } else {
+                ((s - t.height) / self.basin_depth).max(0.0).min(1.0)
+            }
+        }).collect();
+
+        for (i, d) in downstream.iter().enumerate() {
+            if let Some(d) = *d {
+                if !grid.tiles[i].has_water && grid.tiles[d].has_water && grid.tiles[d].height >= sea_level - self.shelf_depth {
+                    let delta = discharge[i] / (discharge[i] + 10.0 * self.river_discharge);
+                    sediment[d] = f64::max(sediment[d], delta);
+                }
+            }
+        }
+
+        sediment
+    }
+}
@@ -0,0 +1,62 @@
+use std::f64;
+use std::f64::consts::PI;
+
+use planet_gen::orbit::Orbit;
+
+/* Spin of the planet about its axis. A tidally locked planet turns once per orbit and keeps one face to the star,
+   swaying only by the libration an eccentric orbit causes */
+#[derive(Copy, Clone, Debug)]
+pub struct Rotation {
+    pub period: f64, // s, sidereal, ignored when tidally locked
+    pub retrograde: bool,
+    pub tidally_locked: bool,
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Rotation {
+            period: 86164.1,
+            retrograde: false,
+            tidally_locked: false,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl Rotation {
+    pub fn tidally_locked() -> Rotation {
+        Rotation {
+            tidally_locked: true,
+            ..Rotation::default()
+        }
+    }
+
+    /* rad/s, negative when retrograde */
+    pub fn angular_velocity(&self, orbit: &Orbit) -> f64 {
+        if self.tidally_locked {
+            orbit.mean_motion()
+        } else {
+            let rate = 2.0 * PI / self.period;
+            if self.retrograde { -rate } else { rate }
+        }
+    }
+
+    /* Angle of the planet about +Y at the given time, radians, see the frame of Orbit */
+    pub fn angle(&self, time: f64, orbit: &Orbit) -> f64 {
+        (self.angular_velocity(orbit) * time).rem_euclid(2.0 * PI)
+    }
+
+    /* Seconds between two noons, infinite when tidally locked */
+    pub fn solar_day(&self, orbit: &Orbit) -> f64 {
+        let rate = self.angular_velocity(orbit) - orbit.mean_motion();
+
+        if rate.abs() < 1e-12 { f64::INFINITY } else { 2.0 * PI / rate.abs() }
+    }
+
+    /* Length of the calendar day: the solar day, or the sidereal period for a locked planet that has none */
+    pub fn day_length(&self, orbit: &Orbit) -> f64 {
+        let solar_day = self.solar_day(orbit);
+
+        if solar_day.is_finite() { solar_day } else { 2.0 * PI / self.angular_velocity(orbit).abs() }
+    }
+}
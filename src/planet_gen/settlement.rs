@@ -0,0 +1,389 @@
+extern crate cgmath;
+
+use std::f64;
+use std::collections::BinaryHeap;
+
+use self::cgmath::Vector3;
+
+use sphere_grid::grid::Grid;
+use sphere_grid::tile::GridTile;
+use sphere_grid::sphere::Sphere;
+use sphere_grid::visit::Visit;
+
+/* Town founded on a tile, spreading over neighbouring ones (PlanetTile::settlement) as it grows */
+#[derive(Clone, Debug)]
+pub struct Settlement {
+    pub id: usize,
+    pub tile: usize,
+    pub position: Vector3<f32>,
+    pub population: f64,
+    pub tiles: Vec<usize>,
+}
+
+/* Cheapest way over land between two settlements, its tiles are marked with PlanetTile::route */
+#[derive(Clone, Debug)]
+pub struct Route {
+    pub from: usize,
+    pub to: usize,
+    pub tiles: Vec<usize>,
+    pub length: f64, // m
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Settlements {
+    pub settlements: Vec<Settlement>,
+    pub routes: Vec<Route>,
+}
+
+#[allow(dead_code)]
+impl Settlements {
+    pub fn population(&self) -> f64 {
+        self.settlements.iter().map(|s| s.population).sum()
+    }
+
+    /* m of all routes together */
+    pub fn route_length(&self) -> f64 {
+        self.routes.iter().map(|r| r.length).sum()
+    }
+
+    /* Gives up the tiles the sea has taken after the sea level changed. Their people are lost, settlements left
+       without land are abandoned and routes through the water are dropped */
+    pub fn flood(&mut self, grid: &mut Grid) {
+        for t in &mut grid.tiles {
+            if t.has_water {
+                t.habitability = 0.0;
+                t.settlement = None;
+                t.population = 0.0;
+            }
+            t.route = false;
+        }
+
+        for s in &mut self.settlements {
+            s.tiles.retain(|i| !grid.tiles[*i].has_water);
+            s.population = s.tiles.iter().map(|i| grid.tiles[*i].population).sum();
+
+            if grid.tiles[s.tile].has_water {
+                if let Some(&tile) = s.tiles.first() {
+                    s.tile = tile;
+                    s.position = grid.tiles[tile].grid_tile.pos;
+                }
+            }
+        }
+        self.settlements.retain(|s| !s.tiles.is_empty());
+
+        let settlements = &self.settlements;
+        self.routes.retain(|r| {
+            let settled = |id: usize| settlements.iter().any(|s| s.id == id);
+            settled(r.from) && settled(r.to) && r.tiles.iter().all(|i| !grid.tiles[*i].has_water)
+        });
+
+        for route in self.routes.iter() {
+            for i in route.tiles.iter() {
+                grid.tiles[*i].route = true;
+            }
+        }
+    }
+
+    /* Most populous first */
+    pub fn largest(&self, count: usize) -> Vec<&Settlement> {
+        let mut largest: Vec<&Settlement> = self.settlements.iter().collect();
+        largest.sort_by(|a, b| b.population.partial_cmp(&a.population).unwrap());
+        largest.truncate(count);
+        largest
+    }
+}
+
+/* Rule based settling on the mean climate (PlanetTile::mean_air_temperature, mean_precipitation). Habitability
+   (PlanetTile::habitability, 0..1) is the product of a comfortable temperature, fresh water nearby (sea, lakes and
+   rivers), gentle slopes and fertile land, with a bonus for ore or oil (PlanetTile::deposits). Settlements are
+   founded on the best tiles at least spacing apart, grow logistically towards what their tiles can feed and claim
+   the best free neighbour whenever they get crowded. Finally each is connected to its nearest neighbours on the
+   same landmass */
+#[derive(Copy, Clone, Debug)]
+pub struct SettlementModel {
+    pub optimal_temperature: f64, // °C
+    pub temperature_range: f64, // °C either side of the optimum that is still livable
+    pub water_distance: f64, // m from a coast or lake where the water score falls to 1/e
+    pub rain_half_score: f64, // kg/m^2/s of precipitation for half the water score inland
+    pub slope_half_score: f64, // rise over run halving the slope score and doubling the route cost
+    pub fertile_biomass: f64, // kg/m^2 of plants for full fertility
+    pub mineral_bonus: f64, // extra habitability on ore and oil deposits
+    pub min_habitability: f64, // to found a settlement
+
+    pub count: usize,
+    pub spacing: f64, // m between founding tiles
+    pub initial_population: f64,
+    pub max_density: f64, // people per km^2 at habitability 1
+    pub growth_rate: f64, // 1/year
+    pub crowding: f64, // share of the capacity at which a settlement spreads
+    pub years: usize,
+    pub route_neighbours: usize,
+}
+
+impl Default for SettlementModel {
+    fn default() -> Self {
+        SettlementModel {
+            optimal_temperature: 18.0,
+            temperature_range: 25.0,
+            water_distance: 150000.0,
+            rain_half_score: 1.5e-5,
+            slope_half_score: 0.02,
+            fertile_biomass: 5.0,
+            mineral_bonus: 0.25,
+            min_habitability: 0.3,
+
+            count: 30,
+            spacing: 500000.0,
+            initial_population: 1000.0,
+            max_density: 100.0,
+            growth_rate: 0.01,
+            crowding: 0.8,
+            years: 1000,
+            route_neighbours: 2,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl SettlementModel {
+    pub fn generate(&self, grid: &mut Grid, planet_radius: f64, sea_level: f64) -> Settlements {
+        for t in &mut grid.tiles {
+            t.settlement = None;
+            t.population = 0.0;
+            t.route = false;
+        }
+
+        self.score(grid, planet_radius, sea_level);
+
+        let mut settlements = self.found(grid, planet_radius);
+        for _ in 0..self.years {
+            self.grow(grid, &mut settlements, planet_radius);
+        }
+
+        let routes = self.connect(grid, &settlements, planet_radius, sea_level);
+
+        Settlements { settlements: settlements, routes: routes }
+    }
+
+    /* Sets PlanetTile::habitability */
+    pub fn score(&self, grid: &mut Grid, planet_radius: f64, sea_level: f64) {
+        let sources: Vec<usize> = (0..grid.tiles.len())
+            .filter(|i| grid.tiles[*i].has_water || grid.tiles[*i].deposits.fresh_water > 0.0)
+            .collect();
+        let (water_distance, _) = shortest_paths(grid, &sources, None, |a, b| Some(tile_distance(grid, a, b, planet_radius)));
+
+        let slopes: Vec<f64> = (0..grid.tiles.len()).map(|i| {
+            let tile = &grid.tiles[i].grid_tile;
+
+            (0..tile.edge_count as usize)
+                .map(|k| slope(grid, i, GridTile::get_id(tile.tiles[k]), planet_radius, sea_level))
+                .fold(0.0, f64::max)
+        }).collect();
+
+        for (i, t) in grid.tiles.iter_mut().enumerate() {
+            if t.has_water || t.ice_thickness > 1.0 {
+                t.habitability = 0.0;
+                continue;
+            }
+
+            let climate = f64::max(0.0, 1.0 - ((t.mean_air_temperature - self.optimal_temperature) / self.temperature_range).powi(2));
+            let rain = t.mean_precipitation / (t.mean_precipitation + self.rain_half_score);
+            let water = f64::max((-water_distance[i] / self.water_distance).exp(), rain);
+            let gentle = 1.0 / (1.0 + slopes[i] / self.slope_half_score);
+            let area = t.grid_tile.area() as f64 * planet_radius * planet_radius / 1.0e6; // km^2
+            let arable = f64::max(t.biomass / self.fertile_biomass, t.deposits.fertile_soil / area);
+            let fertility = 0.2 + 0.8 * f64::min(1.0, arable);
+            let minerals = if t.deposits.ore > 0.0 || t.deposits.oil > 0.0 { 1.0 + self.mineral_bonus } else { 1.0 };
+
+            t.habitability = f64::min(1.0, climate * water * gentle * fertility * minerals);
+        }
+    }
+
+    /* People the tile feeds */
+    pub fn capacity(&self, grid: &Grid, tile: usize, planet_radius: f64) -> f64 {
+        let t = &grid.tiles[tile];
+        let area = t.grid_tile.area() as f64 * planet_radius * planet_radius / 1.0e6; // km^2
+
+        self.max_density * t.habitability * area
+    }
+
+    /* Best tiles first, skipping those too close to an earlier settlement */
+    fn found(&self, grid: &mut Grid, planet_radius: f64) -> Vec<Settlement> {
+        let mut candidates: Vec<usize> = (0..grid.tiles.len())
+            .filter(|i| grid.tiles[*i].habitability >= self.min_habitability)
+            .collect();
+        candidates.sort_by(|a, b| grid.tiles[*b].habitability.partial_cmp(&grid.tiles[*a].habitability).unwrap());
+
+        let mut settlements: Vec<Settlement> = Vec::new();
+
+        for i in candidates {
+            if settlements.len() >= self.count {
+                break;
+            }
+
+            let pos = grid.tiles[i].grid_tile.pos;
+            if settlements.iter().any(|s| Sphere::geodesic_distance(s.position, pos, planet_radius) < self.spacing) {
+                continue;
+            }
+
+            grid.tiles[i].settlement = Some(settlements.len());
+            grid.tiles[i].population = self.initial_population;
+
+            settlements.push(Settlement {
+                id: settlements.len(),
+                tile: i,
+                position: pos,
+                population: self.initial_population,
+                tiles: vec![i],
+            });
+        }
+
+        settlements
+    }
+
+    /* One year of growth, spreading and sharing the population out over the tiles */
+    fn grow(&self, grid: &mut Grid, settlements: &mut Vec<Settlement>, planet_radius: f64) {
+        for s in settlements.iter_mut() {
+            let capacity: f64 = s.tiles.iter().map(|i| self.capacity(grid, *i, planet_radius)).sum();
+            if capacity <= 0.0 {
+                continue;
+            }
+
+            s.population = f64::max(0.0, s.population + self.growth_rate * s.population * (1.0 - s.population / capacity));
+
+            if s.population > self.crowding * capacity {
+                if let Some(next) = self.best_free_neighbour(grid, &s.tiles) {
+                    grid.tiles[next].settlement = Some(s.id);
+                    s.tiles.push(next);
+                }
+            }
+
+            let capacity: f64 = s.tiles.iter().map(|i| self.capacity(grid, *i, planet_radius)).sum();
+            for i in s.tiles.iter() {
+                grid.tiles[*i].population = s.population * self.capacity(grid, *i, planet_radius) / capacity;
+            }
+        }
+    }
+
+    fn best_free_neighbour(&self, grid: &Grid, tiles: &[usize]) -> Option<usize> {
+        let mut best: Option<usize> = None;
+
+        for i in tiles {
+            let tile = &grid.tiles[*i].grid_tile;
+
+            for k in 0..tile.edge_count as usize {
+                let n = GridTile::get_id(tile.tiles[k]);
+                let t = &grid.tiles[n];
+
+                if t.settlement.is_none() && t.habitability > 0.0 && best.map_or(true, |b| t.habitability > grid.tiles[b].habitability) {
+                    best = Some(n);
+                }
+            }
+        }
+
+        best
+    }
+
+    /* Routes to the nearest route_neighbours settlements on the same landmass, steep ground costs extra */
+    fn connect(&self, grid: &mut Grid, settlements: &[Settlement], planet_radius: f64, sea_level: f64) -> Vec<Route> {
+        let mut routes: Vec<Route> = Vec::new();
+
+        for a in settlements {
+            let region = grid.tiles[a.tile].region;
+
+            let mut others: Vec<&Settlement> = settlements.iter()
+                .filter(|b| b.id != a.id && grid.tiles[b.tile].region == region)
+                .collect();
+            others.sort_by(|x, y| {
+                let dx = Sphere::geodesic_distance(a.position, x.position, planet_radius);
+                let dy = Sphere::geodesic_distance(a.position, y.position, planet_radius);
+                dx.partial_cmp(&dy).unwrap()
+            });
+
+            for b in others.iter().take(self.route_neighbours) {
+                if routes.iter().any(|r| (r.from == a.id && r.to == b.id) || (r.from == b.id && r.to == a.id)) {
+                    continue;
+                }
+
+                let (_, previous) = shortest_paths(grid, &[a.tile], Some(b.tile), |from, to| {
+                    if grid.tiles[to].has_water {
+                        return None;
+                    }
+
+                    let distance = tile_distance(grid, from, to, planet_radius);
+                    Some(distance * (1.0 + slope(grid, from, to, planet_radius, sea_level) / self.slope_half_score))
+                });
+
+                let mut tiles = vec![b.tile];
+                while let Some(p) = previous[*tiles.last().unwrap()] {
+                    tiles.push(p);
+                }
+                tiles.reverse();
+
+                if tiles[0] != a.tile {
+                    continue;
+                }
+
+                let length = tiles.windows(2).map(|w| tile_distance(grid, w[0], w[1], planet_radius)).sum();
+                routes.push(Route { from: a.id, to: b.id, tiles: tiles, length: length });
+            }
+        }
+
+        for route in routes.iter() {
+            for i in route.tiles.iter() {
+                grid.tiles[*i].route = true;
+            }
+        }
+
+        routes
+    }
+}
+
+fn tile_distance(grid: &Grid, a: usize, b: usize, planet_radius: f64) -> f64 {
+    Sphere::geodesic_distance(grid.tiles[a].grid_tile.pos, grid.tiles[b].grid_tile.pos, planet_radius)
+}
+
+/* Rise over run between neighbours, the sea counts as flat */
+fn slope(grid: &Grid, a: usize, b: usize, planet_radius: f64, sea_level: f64) -> f64 {
+    let rise = f64::max(grid.tiles[a].height, sea_level) - f64::max(grid.tiles[b].height, sea_level);
+    rise.abs() / tile_distance(grid, a, b, planet_radius)
+}
+
+/* Dijkstra from several sources over tile neighbours, stopping early at the target. Returns the cost of reaching
+   every tile and the tile it was reached from. A step costing None is impassable */
+fn shortest_paths<F>(grid: &Grid, sources: &[usize], target: Option<usize>, step_cost: F) -> (Vec<f64>, Vec<Option<usize>>)
+    where F: Fn(usize, usize) -> Option<f64>
+{
+    let mut costs = vec![f64::INFINITY; grid.tiles.len()];
+    let mut previous = vec![None; grid.tiles.len()];
+    let mut heap = BinaryHeap::new();
+
+    for s in sources {
+        costs[*s] = 0.0;
+        heap.push(Visit { cost: 0.0, tile: *s });
+    }
+
+    while let Some(Visit { cost, tile }) = heap.pop() {
+        if Some(tile) == target {
+            break;
+        }
+        if cost > costs[tile] {
+            continue;
+        }
+
+        let grid_tile = &grid.tiles[tile].grid_tile;
+        for k in 0..grid_tile.edge_count as usize {
+            let n = GridTile::get_id(grid_tile.tiles[k]);
+
+            if let Some(step) = step_cost(tile, n) {
+                if cost + step < costs[n] {
+                    costs[n] = cost + step;
+                    previous[n] = Some(tile);
+                    heap.push(Visit { cost: cost + step, tile: n });
+                }
+            }
+        }
+    }
+
+    (costs, previous)
+}
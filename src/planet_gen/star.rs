@@ -0,0 +1,23 @@
+use std::f64::consts::PI;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Star {
+    pub luminosity: f64, // W
+}
+
+impl Default for Star {
+    fn default() -> Self {
+        Star {
+            luminosity: Star::SOLAR_LUMINOSITY,
+        }
+    }
+}
+
+impl Star {
+    pub const SOLAR_LUMINOSITY: f64 = 3.828e26; // W
+
+    /* W/m^2 on a surface facing the star at the given distance (m) */
+    pub fn flux(&self, distance: f64) -> f64 {
+        self.luminosity / (4.0 * PI * distance * distance)
+    }
+}
@@ -0,0 +1,103 @@
+use sphere_grid::grid::Grid;
+use planet_gen::material::{ Material, MaterialRegistry };
+
+/* Ground under each tile, the active layer of the tile material (Material::depth) split into layers growing
+   thicker with depth. The top layer is PlanetTile::temperature and takes the surface flux, the ones below are kept
+   in PlanetTile::soil_temperatures and exchange heat by conduction only. Water is a single well mixed layer */
+#[derive(Copy, Clone, Debug)]
+pub struct Subsurface {
+    pub layers: usize,
+    pub layer_growth: f64, // thickness ratio of a layer to the one above it
+}
+
+impl Default for Subsurface {
+    fn default() -> Self {
+        Subsurface {
+            layers: 4,
+            layer_growth: 3.0,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl Subsurface {
+    /* Single layer holding the whole active depth */
+    pub fn single_layer() -> Subsurface {
+        Subsurface {
+            layers: 1,
+            layer_growth: 1.0,
+        }
+    }
+
+    /* Layer thicknesses (m) from the surface down, adding up to the material depth */
+    pub fn layer_thicknesses(&self, material: &Material, has_water: bool) -> Vec<f64> {
+        let layers = if has_water { 1 } else { usize::max(1, self.layers) };
+        let weights: Vec<f64> = (0..layers).map(|i| self.layer_growth.powi(i as i32)).collect();
+        let total: f64 = weights.iter().sum();
+
+        weights.iter().map(|w| material.depth * w / total).collect()
+    }
+
+    /* J / (m^2 K) of the layer taking the surface flux */
+    pub fn surface_heat_capacity(&self, material: &Material, has_water: bool) -> f64 {
+        material.density * material.specific_heat * self.layer_thicknesses(material, has_water)[0]
+    }
+
+    /* surface_heat_capacity of every tile */
+    pub fn surface_heat_capacities(&self, grid: &Grid, materials: &MaterialRegistry) -> Vec<f64> {
+        grid.tiles.iter().map(|t| self.surface_heat_capacity(materials.get(t.material), t.has_water)).collect()
+    }
+
+    /* Applies PlanetTile::surface_flux to the top layer and conducts heat down the column, implicit in time. The
+       surface flux is linearised around the current temperature with PlanetTile::surface_coupling, so a thin top
+       layer settles towards its balance instead of overshooting it on long steps. The bottom of the column is
+       insulated */
+    pub fn update(&self, grid: &mut Grid, materials: &MaterialRegistry, delta_t: f64) {
+        for t in &mut grid.tiles {
+            let material = materials.get(t.material);
+            let thicknesses = self.layer_thicknesses(material, t.has_water);
+            let layers = thicknesses.len();
+
+            if t.soil_temperatures.len() != self.layers.saturating_sub(1) {
+                t.soil_temperatures = vec![t.temperature; self.layers.saturating_sub(1)];
+            }
+
+            let volumetric_heat = material.density * material.specific_heat;
+            let capacities: Vec<f64> = thicknesses.iter().map(|dz| volumetric_heat * dz / delta_t).collect();
+            let conductances: Vec<f64> = (1..layers)
+                .map(|i| material.conductivity / (0.5 * (thicknesses[i - 1] + thicknesses[i])))
+                .collect();
+
+            let mut temperatures = Vec::with_capacity(layers);
+            temperatures.push(t.temperature);
+            temperatures.extend_from_slice(&t.soil_temperatures[..layers - 1]);
+
+            // Tridiagonal system, solved with the Thomas algorithm
+            let mut diagonal = vec![0.0; layers];
+            let mut rhs = vec![0.0; layers];
+            for i in 0..layers {
+                let above = if i > 0 { conductances[i - 1] } else { 0.0 };
+                let below = if i + 1 < layers { conductances[i] } else { 0.0 };
+
+                diagonal[i] = capacities[i] + above + below;
+                rhs[i] = capacities[i] * temperatures[i];
+            }
+            diagonal[0] += t.surface_coupling;
+            rhs[0] += t.surface_flux + t.surface_coupling * temperatures[0];
+
+            for i in 1..layers {
+                let factor = conductances[i - 1] / diagonal[i - 1];
+                diagonal[i] -= factor * conductances[i - 1];
+                rhs[i] += factor * rhs[i - 1];
+            }
+
+            temperatures[layers - 1] = rhs[layers - 1] / diagonal[layers - 1];
+            for i in (0..layers - 1).rev() {
+                temperatures[i] = (rhs[i] + conductances[i] * temperatures[i + 1]) / diagonal[i];
+            }
+
+            t.temperature = temperatures[0];
+            t.soil_temperatures[..layers - 1].copy_from_slice(&temperatures[1..]);
+        }
+    }
+}
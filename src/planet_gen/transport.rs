@@ -0,0 +1,114 @@
+extern crate cgmath;
+
+use cgmath::{ Vector3, InnerSpace };
+
+use sphere_grid::grid::Grid;
+use sphere_grid::tile::GridTile;
+
+pub struct Transport;
+
+impl Transport {
+    /* Never move more than this fraction of a tile's contents in one step, keeps upwind advection stable */
+    const MAX_OUTFLOW: f64 = 0.5;
+
+    /* Upwind advection of a per-area quantity along tangent velocities (m/s) on a sphere of the given radius (m) */
+    pub fn advect(grid: &Grid, values: &[f64], velocities: &[Vector3<f32>], radius: f64, delta_t: f64) -> Vec<f64> {
+        let mut result = values.to_vec();
+
+        for i in 0..grid.tiles.len() {
+            let tile = &grid.tiles[i].grid_tile;
+            let velocity = velocities[i];
+            let speed = velocity.magnitude() as f64;
+
+            if speed == 0.0 || values[i] == 0.0 {
+                continue;
+            }
+
+            let mut weights = [0.0f64; 6];
+            let mut total_weight = 0.0;
+            let mut spacing = 0.0;
+
+            for k in 0..tile.edge_count as usize {
+                let direction = GridTile::get_pos(tile.tiles[k]) - tile.pos;
+                spacing += direction.magnitude() as f64;
+
+                weights[k] = f64::max(0.0, velocity.dot(direction.normalize()) as f64);
+                total_weight += weights[k];
+            }
+
+            if total_weight == 0.0 {
+                continue;
+            }
+
+            spacing = spacing / tile.edge_count as f64 * radius;
+
+            let outflow = f64::min(speed * delta_t / spacing, Transport::MAX_OUTFLOW) * values[i];
+            result[i] -= outflow;
+
+            for k in 0..tile.edge_count as usize {
+                result[GridTile::get_id(tile.tiles[k])] += outflow * weights[k] / total_weight;
+            }
+        }
+
+        result
+    }
+
+    /* Upwind advection of an intensive quantity such as temperature, every tile is mixed with its upwind neighbours */
+    pub fn advect_scalar(grid: &Grid, values: &[f64], velocities: &[Vector3<f32>], radius: f64, delta_t: f64) -> Vec<f64> {
+        Transport::advect_scalar_where(grid, values, velocities, radius, delta_t, |_| true)
+    }
+
+    /* Same as advect_scalar, but only tiles accepted by the filter exchange values (e.g. water tiles for currents) */
+    pub fn advect_scalar_where<F>(grid: &Grid, values: &[f64], velocities: &[Vector3<f32>], radius: f64, delta_t: f64, filter: F) -> Vec<f64>
+                                                                                                        where F: Fn(usize) -> bool {
+        let mut result = values.to_vec();
+
+        for i in 0..grid.tiles.len() {
+            let tile = &grid.tiles[i].grid_tile;
+            let velocity = velocities[i];
+            let speed = velocity.magnitude() as f64;
+
+            if speed == 0.0 || !filter(i) {
+                continue;
+            }
+
+            let mut upwind_value = 0.0;
+            let mut total_weight = 0.0;
+            let mut spacing = 0.0;
+
+            for k in 0..tile.edge_count as usize {
+                let n = GridTile::get_id(tile.tiles[k]);
+                let direction = GridTile::get_pos(tile.tiles[k]) - tile.pos;
+                spacing += direction.magnitude() as f64;
+
+                if !filter(n) {
+                    continue;
+                }
+
+                let weight = f64::max(0.0, -velocity.dot(direction.normalize()) as f64);
+                upwind_value += weight * values[n];
+                total_weight += weight;
+            }
+
+            if total_weight == 0.0 {
+                continue;
+            }
+
+            spacing = spacing / tile.edge_count as f64 * radius;
+
+            let mixing = f64::min(speed * delta_t / spacing, Transport::MAX_OUTFLOW);
+            result[i] += mixing * (upwind_value / total_weight - values[i]);
+        }
+
+        result
+    }
+
+    /* Local east and north unit vectors at a point of the unit sphere, rotation is about +Y */
+    pub fn local_basis(pos: Vector3<f32>) -> (Vector3<f32>, Vector3<f32>) {
+        let east = Vector3::unit_y().cross(pos);
+        let east = if east.magnitude2() < 1e-12 { Vector3::unit_x() } else { east.normalize() };
+        let north = pos.cross(east).normalize();
+
+        (east, north)
+    }
+}
@@ -0,0 +1,92 @@
+use std::f64;
+
+use sphere_grid::grid::Grid;
+use planet_gen::moisture::MoistureModel;
+
+/* Plant biomass on land (PlanetTile::biomass, kg/m^2) growing with warmth, humid air, light and soil water, and
+   dying back in frost and drought. Cover (PlanetTile::vegetation, 0..1) closes quickly with the first biomass, as
+   grass does, while forests keep adding biomass for years. Rain fills PlanetTile::soil_water, which the plants
+   transpire back into the air */
+#[derive(Copy, Clone, Debug)]
+pub struct VegetationModel {
+    pub optimal_temperature: f64, // °C
+    pub temperature_range: f64, // °C either side of the optimum where plants still grow
+    pub humidity_half_growth: f64, // humidity at which growth is halved
+    pub light_half_growth: f64, // W/m^2 of sunlight at which growth is halved
+
+    pub growth_rate: f64, // 1/s, relative biomass growth in ideal conditions
+    pub turnover_rate: f64, // 1/s, biomass lost to old age and grazing
+    pub dieback_rate: f64, // 1/s, biomass lost when it is too cold or too dry to grow
+    pub max_biomass: f64, // kg/m^2, of a mature rainforest
+    pub seed_biomass: f64, // kg/m^2, always present where plants could grow
+    pub cover_biomass: f64, // kg/m^2 for about two thirds cover
+
+    pub field_capacity: f64, // kg/m^2 of water the soil holds
+    pub transpiration_rate: f64, // kg/m^2/s under full cover, sunlight and dry air
+    pub albedo: f64,
+}
+
+impl Default for VegetationModel {
+    fn default() -> Self {
+        VegetationModel {
+            optimal_temperature: 25.0,
+            temperature_range: 25.0,
+            humidity_half_growth: 0.3,
+            light_half_growth: 200.0,
+
+            growth_rate: 4.0e-7,
+            turnover_rate: 1.5e-8,
+            dieback_rate: 3.0e-7,
+            max_biomass: 40.0,
+            seed_biomass: 0.01,
+            cover_biomass: 1.5,
+
+            field_capacity: 150.0,
+            transpiration_rate: 5.0e-5,
+            albedo: 0.15,
+        }
+    }
+}
+
+impl VegetationModel {
+    pub fn update(&self, grid: &mut Grid, moisture_model: &MoistureModel, delta_t: f64) {
+        for t in &mut grid.tiles {
+            if t.has_water {
+                t.biomass = 0.0;
+                t.vegetation = 0.0;
+                t.soil_water = 0.0;
+                continue;
+            }
+
+            t.soil_water = f64::min(self.field_capacity, t.soil_water + t.precipitation * delta_t);
+
+            let warmth = f64::max(0.0, 1.0 - ((t.air_temperature - self.optimal_temperature) / self.temperature_range).powi(2));
+            let air = (1.0 + self.humidity_half_growth) * t.humidity / (t.humidity + self.humidity_half_growth);
+            let light = t.insolation / (t.insolation + self.light_half_growth);
+            let water = t.soil_water / self.field_capacity;
+            let buried = t.ice_thickness > 0.0;
+
+            let growth = if buried { 0.0 } else { self.growth_rate * warmth * air * light * water };
+            let stress = if buried || warmth == 0.0 || water < 0.05 { self.dieback_rate } else { 0.0 };
+
+            let biomass = if warmth > 0.0 { f64::max(t.biomass, self.seed_biomass) } else { t.biomass };
+            let change = growth * biomass * (1.0 - biomass / self.max_biomass) - (self.turnover_rate + stress) * biomass;
+            t.biomass = f64::max(0.0, biomass + change * delta_t);
+            t.vegetation = 1.0 - (-t.biomass / self.cover_biomass).exp();
+
+            /* Transpiration moves soil water into the air column */
+            let demand = f64::max(0.0, 1.0 - t.humidity);
+            let room = f64::max(0.0, moisture_model.saturation(t.air_temperature) - t.moisture);
+            let transpired = f64::min(f64::min(t.soil_water, room), self.transpiration_rate * t.vegetation * light * demand * water * delta_t);
+            t.soil_water -= transpired;
+            t.moisture += transpired;
+        }
+    }
+
+    /* Blends the vegetation into the material albedo, before snow covers both */
+    pub fn apply_albedo(&self, grid: &mut Grid) {
+        for t in &mut grid.tiles {
+            t.albedo = (1.0 - t.vegetation) * t.albedo + t.vegetation * self.albedo;
+        }
+    }
+}
@@ -0,0 +1,100 @@
+extern crate cgmath;
+extern crate rand;
+
+use std::f64;
+use std::f64::consts::PI;
+
+use self::cgmath::{ Vector3, InnerSpace };
+use self::rand::{ Rng, SeedableRng };
+use self::rand::rngs::StdRng;
+
+use sphere_grid::grid::Grid;
+use sphere_grid::sphere::Sphere;
+use planet_gen::plates::Plates;
+
+/* Mantle plume fixed under the moving plates */
+#[derive(Copy, Clone, Debug)]
+pub struct Hotspot {
+    pub position: Vector3<f32>,
+    pub peak_height: f64, // m, of a fresh volcano
+    pub radius: f64, // m, of the volcano base
+}
+
+/* Builds a volcano over every hotspot each interval for the last duration years. The plates carry the older ones
+   away, so they line up in chains growing older and lower (subsidence_time) away from the plume.
+   Sets PlanetTile::volcanic_age (years since the last eruption) and volcanic_activity (1 over the plume) */
+#[derive(Clone, Debug)]
+pub struct Volcanism {
+    pub hotspots: Vec<Hotspot>,
+    pub duration: f64, // years
+    pub interval: f64, // years between volcanoes
+    pub subsidence_time: f64, // years for a volcano to sink to 1/e of its height
+    pub active_time: f64, // years for the activity to fall to 1/e
+}
+
+#[allow(dead_code)]
+impl Volcanism {
+    pub fn new(hotspots: Vec<Hotspot>) -> Volcanism {
+        Volcanism {
+            hotspots: hotspots,
+            duration: 70.0e6,
+            interval: 1.0e6,
+            subsidence_time: 25.0e6,
+            active_time: 1.5e6,
+        }
+    }
+
+    pub fn random(count: usize, seed: u64, peak_height: f64) -> Volcanism {
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        Volcanism::new((0..count).map(|_| {
+            Hotspot {
+                position: Sphere::random_direction(&mut rng),
+                peak_height: peak_height * rng.gen_range(0.6, 1.0),
+                radius: rng.gen_range(60000.0, 120000.0),
+            }
+        }).collect())
+    }
+
+    pub fn apply(&self, grid: &mut Grid, plates: &Plates, planet_radius: f64) {
+        for t in &mut grid.tiles {
+            t.volcanic_age = f64::INFINITY;
+            t.volcanic_activity = 0.0;
+        }
+
+        let steps = (self.duration / self.interval).floor() as usize;
+
+        for hotspot in &self.hotspots {
+            let plate = plates.plate_at(hotspot.position);
+
+            for step in 0..steps + 1 {
+                let age = step as f64 * self.interval;
+                let centre = plates.carry(plate, hotspot.position, age);
+                let height = hotspot.peak_height * (-age / self.subsidence_time).exp();
+
+                self.build_volcano(grid, centre, height, hotspot.radius / planet_radius, age);
+            }
+        }
+    }
+
+    fn build_volcano(&self, grid: &mut Grid, centre: Vector3<f32>, height: f64, radius: f64, age: f64) {
+        let cos_reach = f64::min(PI, 2.5 * radius).cos();
+        let activity = (-age / self.active_time).exp();
+
+        for t in &mut grid.tiles {
+            let cos = t.grid_tile.pos.dot(centre) as f64;
+            if cos < cos_reach {
+                continue;
+            }
+
+            let distance = cos.max(-1.0).min(1.0).acos() / radius;
+            let shape = (-distance * distance).exp();
+            t.height += height * shape;
+
+            if shape > 0.3 {
+                t.volcanic_age = f64::min(t.volcanic_age, age);
+                t.volcanic_activity = f64::max(t.volcanic_activity, activity * shape);
+            }
+        }
+    }
+}
@@ -1,4 +1,8 @@
+extern crate cgmath;
+
 use std::ptr;
+use self::cgmath::InnerSpace;
+
 use tile::GridTile;
 use corner::Corner;
 
@@ -16,4 +20,9 @@ impl Edge {
             corners: [ptr::null(); 2],
         }
     }
+
+    /* Length on the unit sphere */
+    pub fn get_length(edge: *const Edge) -> f32 {
+        unsafe { (Corner::get_pos((*edge).corners[1]) - Corner::get_pos((*edge).corners[0])).magnitude() }
+    }
 }
\ No newline at end of file
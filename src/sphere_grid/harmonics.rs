@@ -0,0 +1,231 @@
+extern crate cgmath;
+extern crate rand;
+
+use std::f64::consts::PI;
+
+use self::cgmath::Vector3;
+use self::rand::SeedableRng;
+use self::rand::rngs::StdRng;
+
+use grid::Grid;
+use tile::PlanetTile;
+use random::Random;
+
+/* Real spherical harmonic expansion of a scalar field, orthonormal over the sphere. The polar axis is +Y, the spin
+   axis of the planet. Coefficient (l, m) is stored at l * l + l + m, negative m are the sine terms */
+#[derive(Clone, Debug)]
+pub struct SphericalHarmonics {
+    pub degree: usize,
+    pub coefficients: Vec<f64>,
+}
+
+#[allow(dead_code)]
+impl SphericalHarmonics {
+    pub fn new(degree: usize) -> SphericalHarmonics {
+        SphericalHarmonics {
+            degree: degree,
+            coefficients: vec![0.0; (degree + 1) * (degree + 1)],
+        }
+    }
+
+    pub fn index(l: usize, m: isize) -> usize {
+        ((l * l + l) as isize + m) as usize
+    }
+
+    pub fn get(&self, l: usize, m: isize) -> f64 {
+        self.coefficients[Self::index(l, m)]
+    }
+
+    pub fn set(&mut self, l: usize, m: isize, value: f64) {
+        self.coefficients[Self::index(l, m)] = value;
+    }
+
+    /* Projects the field onto the harmonics up to degree, tile areas are the quadrature weights */
+    pub fn analyse<F: Fn(&PlanetTile) -> f64>(grid: &Grid, degree: usize, field: F) -> SphericalHarmonics {
+        let mut harmonics = SphericalHarmonics::new(degree);
+        let total_area: f64 = grid.tiles.iter().map(|t| t.grid_tile.area() as f64).sum();
+
+        let mut basis = vec![0.0; harmonics.coefficients.len()];
+        for t in &grid.tiles {
+            let weight = field(t) * t.grid_tile.area() as f64 / total_area * 4.0 * PI;
+            Self::basis(degree, t.grid_tile.pos, &mut basis);
+
+            for (c, y) in harmonics.coefficients.iter_mut().zip(basis.iter()) {
+                *c += weight * y;
+            }
+        }
+
+        harmonics
+    }
+
+    /* Random field whose power per degree falls off as l^-exponent, with the given variance over the sphere.
+       Earth's topography is close to exponent 2 */
+    pub fn random(degree: usize, exponent: f64, variance: f64, seed: u64) -> SphericalHarmonics {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut harmonics = SphericalHarmonics::new(degree);
+
+        let total_power: f64 = (1..degree + 1).map(|l| (l as f64).powf(-exponent)).sum();
+
+        for l in 1..degree + 1 {
+            let power = variance * 4.0 * PI * (l as f64).powf(-exponent) / total_power;
+            let deviation = (power / (2 * l + 1) as f64).sqrt();
+
+            for m in -(l as isize)..(l as isize + 1) {
+                harmonics.set(l, m, deviation * Random::gaussian(&mut rng));
+            }
+        }
+
+        harmonics
+    }
+
+    pub fn evaluate(&self, pos: Vector3<f32>) -> f64 {
+        let mut basis = vec![0.0; self.coefficients.len()];
+        Self::basis(self.degree, pos, &mut basis);
+
+        self.coefficients.iter().zip(basis.iter()).map(|(c, y)| c * y).sum()
+    }
+
+    /* Field value of every tile */
+    pub fn synthesise(&self, grid: &Grid) -> Vec<f64> {
+        let mut basis = vec![0.0; self.coefficients.len()];
+
+        grid.tiles.iter().map(|t| {
+            Self::basis(self.degree, t.grid_tile.pos, &mut basis);
+            self.coefficients.iter().zip(basis.iter()).map(|(c, y)| c * y).sum()
+        }).collect()
+    }
+
+    /* Sum of the squared coefficients of each degree */
+    pub fn power_spectrum(&self) -> Vec<f64> {
+        (0..self.degree + 1).map(|l| {
+            (-(l as isize)..(l as isize + 1)).map(|m| self.get(l, m).powi(2)).sum()
+        }).collect()
+    }
+
+    /* Exponent of the power law best fitting the spectrum from degree 1 up, to compare a field with a target
+       spectrum such as Earth's topography (about 2) */
+    pub fn spectral_slope(&self) -> f64 {
+        let points: Vec<(f64, f64)> = self.power_spectrum().iter().enumerate().skip(1)
+            .filter(|&(_, p)| *p > 0.0)
+            .map(|(l, p)| ((l as f64).ln(), p.ln()))
+            .collect();
+
+        let n = points.len() as f64;
+        if n < 2.0 {
+            return 0.0;
+        }
+
+        let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let covariance: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+        let variance: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+
+        -covariance / variance
+    }
+
+    /* Drops everything above the cutoff degree, tapering the last degrees with a cosine so no ringing appears */
+    pub fn low_pass(&self, cutoff: usize, taper: usize) -> SphericalHarmonics {
+        let mut harmonics = self.clone();
+
+        for l in 0..self.degree + 1 {
+            let factor = if l > cutoff {
+                0.0
+            } else if taper > 0 && l + taper > cutoff {
+                let x = (l + taper - cutoff) as f64 / (taper + 1) as f64;
+                0.5 * (1.0 + (PI * x).cos())
+            } else {
+                1.0
+            };
+
+            for m in -(l as isize)..(l as isize + 1) {
+                let value = harmonics.get(l, m) * factor;
+                harmonics.set(l, m, value);
+            }
+        }
+
+        harmonics
+    }
+
+    /* Smooths a tile field in place, keeping the degrees up to cutoff */
+    pub fn filter<G, S>(grid: &mut Grid, cutoff: usize, get: G, mut set: S)
+        where G: Fn(&PlanetTile) -> f64, S: FnMut(&mut PlanetTile, f64) {
+
+        let filtered = Self::analyse(grid, cutoff, get).low_pass(cutoff, cutoff / 4).synthesise(grid);
+
+        for (t, value) in grid.tiles.iter_mut().zip(filtered) {
+            set(t, value);
+        }
+    }
+
+    /* Values of all harmonics up to degree at pos, fully normalised associated Legendre functions
+       built by the usual recurrences in l and m */
+    fn basis(degree: usize, pos: Vector3<f32>, out: &mut [f64]) {
+        let x = (pos.y as f64).max(-1.0).min(1.0); // cos of the colatitude
+        let s = (1.0 - x * x).sqrt();
+        let longitude = (pos.z as f64).atan2(pos.x as f64);
+
+        let mut p_mm = (1.0 / (4.0 * PI)).sqrt();
+
+        for m in 0..degree + 1 {
+            if m > 0 {
+                p_mm *= ((2 * m + 1) as f64 / (2 * m) as f64).sqrt() * s;
+            }
+
+            let (cos_m, sin_m) = if m == 0 {
+                (1.0, 0.0)
+            } else {
+                let angle = m as f64 * longitude;
+                (2.0f64.sqrt() * angle.cos(), 2.0f64.sqrt() * angle.sin())
+            };
+
+            let mut p_prev = 0.0;
+            let mut p = p_mm;
+
+            for l in m..degree + 1 {
+                if l == m + 1 {
+                    p_prev = p;
+                    p = ((2 * m + 3) as f64).sqrt() * x * p_mm;
+                } else if l > m + 1 {
+                    let (lf, mf) = (l as f64, m as f64);
+                    let a = ((4.0 * lf * lf - 1.0) / (lf * lf - mf * mf)).sqrt();
+                    let b = (((lf - 1.0) * (lf - 1.0) - mf * mf) / (4.0 * (lf - 1.0) * (lf - 1.0) - 1.0)).sqrt();
+
+                    let next = a * (x * p - b * p_prev);
+                    p_prev = p;
+                    p = next;
+                }
+
+                out[Self::index(l, m as isize)] = p * cos_m;
+                if m > 0 {
+                    out[Self::index(l, -(m as isize))] = p * sin_m;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use grid::Grid;
+    use super::SphericalHarmonics;
+
+    #[test]
+    fn analyse_recovers_synthesised_coefficients() {
+        let grid = Grid::create_size_n_grid(5);
+        let original = SphericalHarmonics::random(6, 2.0, 1.0, 7);
+
+        let values = original.synthesise(&grid);
+        let recovered = SphericalHarmonics::analyse(&grid, 6, |t| values[t.grid_tile.id]);
+
+        for (a, b) in original.coefficients.iter().zip(recovered.coefficients.iter()) {
+            assert!((a - b).abs() < 0.01, "{} recovered as {}", a, b);
+        }
+    }
+
+    #[test]
+    fn random_field_has_the_requested_slope() {
+        let harmonics = SphericalHarmonics::random(40, 2.0, 1.0, 3);
+
+        assert!((harmonics.spectral_slope() - 2.0).abs() < 0.2, "slope {}", harmonics.spectral_slope());
+    }
+}
@@ -2,6 +2,10 @@ pub mod corner;
 pub mod edge;
 pub mod grid;
 pub mod grid_mesh;
+pub mod harmonics;
+pub mod random;
+pub mod sphere;
 pub mod tile;
+pub mod visit;
 
 pub use self::grid::Grid;
\ No newline at end of file
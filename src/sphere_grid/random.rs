@@ -0,0 +1,19 @@
+extern crate rand;
+
+use std::f64::consts::PI;
+
+use self::rand::Rng;
+use self::rand::rngs::StdRng;
+
+/* Random numbers beyond the uniform ones of rand */
+pub struct Random;
+
+impl Random {
+    /* Standard normal deviate, Box-Muller */
+    pub fn gaussian(rng: &mut StdRng) -> f64 {
+        let u: f64 = rng.gen_range(1e-12, 1.0);
+        let v: f64 = rng.gen();
+
+        (-2.0 * u.ln()).sqrt() * (2.0 * PI * v).cos()
+    }
+}
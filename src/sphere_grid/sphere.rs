@@ -0,0 +1,28 @@
+extern crate cgmath;
+extern crate rand;
+
+use std::f64::consts::PI;
+
+use self::cgmath::{ Vector3, InnerSpace };
+use self::rand::Rng;
+use self::rand::rngs::StdRng;
+
+/* Helpers for points on the unit sphere */
+pub struct Sphere;
+
+impl Sphere {
+    /* Uniformly distributed over the surface */
+    pub fn random_direction(rng: &mut StdRng) -> Vector3<f32> {
+        let y: f64 = rng.gen_range(-1.0, 1.0);
+        let longitude: f64 = rng.gen_range(0.0, 2.0 * PI);
+        let r = (1.0 - y * y).sqrt();
+
+        Vector3::new((r * longitude.cos()) as f32, y as f32, (r * longitude.sin()) as f32)
+    }
+
+    /* m along the surface of a sphere of the given radius */
+    pub fn geodesic_distance(a: Vector3<f32>, b: Vector3<f32>, radius: f64) -> f64 {
+        let cos = (a.dot(b) as f64).max(-1.0).min(1.0);
+        cos.acos() * radius
+    }
+}
@@ -1,53 +1,52 @@
 extern crate cgmath;
 
 use std::ptr;
-use self::cgmath::Vector3;
+use self::cgmath::{Vector3, InnerSpace};
 
 use corner::Corner;
 use edge::Edge;
-
-pub struct PlanetCoreMaterial {
-    specific_heat: f64, // J / kg
-    density: f64, // kg / m^3
-    emissivity: f64,
-}
-
-impl PlanetCoreMaterial {
-    pub const GRANITE: PlanetCoreMaterial = PlanetCoreMaterial {
-        specific_heat: 280.0,
-        density: 2750.0,
-        emissivity: 0.9,
-    };
-
-    pub const WATER: PlanetCoreMaterial = PlanetCoreMaterial {
-        specific_heat: 4180.0,
-        density: 1000.0,
-        emissivity: 0.92,
-    };
-
-    pub fn specific_heat(&self) -> f64 {
-        self.specific_heat
-    }
-
-    pub fn density(&self) -> f64 {
-        self.density
-    }
-
-    pub fn emissivity(&self) -> f64 {
-        self.emissivity
-    }
-}
+use planet_gen::biome::Biome;
+use planet_gen::material::MaterialId;
+use planet_gen::resources::Deposits;
 
 pub struct PlanetTile {
     pub grid_tile: GridTile,
 
-    pub core_material: &'static PlanetCoreMaterial,
+    pub material: MaterialId,
+    pub albedo: f64,
     pub height: f64,
     pub brightness: f32,
+    pub insolation: f64,
     pub temperature: f64,
+    pub surface_flux: f64,
+    pub surface_coupling: f64,
+    pub soil_temperatures: Vec<f64>,
+    pub atmosphere_temperatures: Vec<f64>,
+    pub air_temperature: f64,
+    pub mean_air_temperature: f64,
+    pub pressure: f64,
     pub humidity: f64,
+    pub moisture: f64,
+    pub precipitation: f64,
+    pub mean_precipitation: f64,
+    pub wind: Vector3<f32>,
+    pub current: Vector3<f32>,
     pub has_water: bool,
+    pub region: usize,
+    pub plate: usize,
+    pub volcanic_age: f64,
+    pub volcanic_activity: f64,
     pub has_clouds: bool,
+    pub ice_thickness: f64,
+    pub soil_water: f64,
+    pub biomass: f64,
+    pub vegetation: f64,
+    pub habitability: f64,
+    pub settlement: Option<usize>,
+    pub population: f64,
+    pub route: bool,
+    pub deposits: Deposits,
+    pub biome: Biome,
 }
 
 pub struct GridTile {
@@ -64,13 +63,41 @@ impl PlanetTile {
     pub fn new(grid_tile: GridTile) -> PlanetTile {
         PlanetTile {
             grid_tile: grid_tile,
-            core_material: &PlanetCoreMaterial::GRANITE,
+            material: 0,
+            albedo: 0.3,
             height: 0.0,
             brightness: 0.0,
+            insolation: 0.0,
             temperature: 0.0,
+            surface_flux: 0.0,
+            surface_coupling: 0.0,
+            soil_temperatures: Vec::new(),
+            atmosphere_temperatures: Vec::new(),
+            air_temperature: 0.0,
+            mean_air_temperature: 0.0,
+            pressure: 0.0,
             humidity: 0.0,
+            moisture: 0.0,
+            precipitation: 0.0,
+            mean_precipitation: 0.0,
+            wind: Vector3::new(0.0, 0.0, 0.0),
+            current: Vector3::new(0.0, 0.0, 0.0),
             has_water: false,
+            region: 0,
+            plate: 0,
+            volcanic_age: f64::INFINITY,
+            volcanic_activity: 0.0,
             has_clouds: false,
+            ice_thickness: 0.0,
+            soil_water: 0.0,
+            biomass: 0.0,
+            vegetation: 0.0,
+            habitability: 0.0,
+            settlement: None,
+            population: 0.0,
+            route: false,
+            deposits: Deposits::default(),
+            biome: Biome::Desert,
         }
     }
 }
@@ -88,6 +115,27 @@ impl GridTile {
         }
     }
 
+    pub fn get_id(tile: *const GridTile) -> usize {
+        unsafe { (*tile).id }
+    }
+
+    pub fn get_pos(tile: *const GridTile) -> Vector3<f32> {
+        unsafe { (*tile).pos }
+    }
+
+    /* Area on the unit sphere, sum of the triangles between the tile center and its corners */
+    pub fn area(&self) -> f32 {
+        let mut area = 0.0;
+
+        for k in 0..self.edge_count as usize {
+            let a = Corner::get_pos(self.corners[k]) - self.pos;
+            let b = Corner::get_pos(self.corners[(k + 1) % self.edge_count as usize]) - self.pos;
+            area += a.cross(b).magnitude() * 0.5;
+        }
+
+        area
+    }
+
     pub fn get_tile_pos(&self, n: *const GridTile) -> Option<usize> {
         for i in 0..self.edge_count as usize {
             if self.tiles[i] == n {
@@ -0,0 +1,23 @@
+use std::cmp::Ordering;
+
+/* Tile waiting in a BinaryHeap of a search over the grid, the cheapest one is popped first */
+#[derive(PartialEq)]
+pub struct Visit {
+    pub cost: f64,
+    pub tile: usize,
+}
+
+impl Eq for Visit {}
+
+/* Reversed, so the heap pops the cheapest visit first */
+impl Ord for Visit {
+    fn cmp(&self, other: &Visit) -> Ordering {
+        other.cost.partial_cmp(&self.cost).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Visit) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}